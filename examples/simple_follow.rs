@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use smooth_bevy_cameras::{
+    controllers::follow::{
+        FollowCameraBundle, FollowCameraController, FollowCameraPlugin, FollowTarget,
+    },
+    LookTransformPlugin,
+};
+
+fn main() {
+    App::build()
+        .insert_resource(Msaa { samples: 4 })
+        .add_plugins(DefaultPlugins)
+        .add_plugin(LookTransformPlugin)
+        .add_plugin(FollowCameraPlugin)
+        .add_startup_system(setup.system())
+        .add_system(move_player_system.system())
+        .run();
+}
+
+struct Player;
+
+/// set up a simple 3D scene with a moving cube
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // plane
+    commands.spawn_bundle(PbrBundle {
+        mesh: meshes.add(Mesh::from(shape::Plane { size: 20.0 })),
+        material: materials.add(Color::rgb(0.3, 0.5, 0.3).into()),
+        ..Default::default()
+    });
+
+    // cube
+    let player = commands
+        .spawn_bundle(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+            material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+            transform: Transform::from_xyz(5.0, 0.5, 0.0),
+            ..Default::default()
+        })
+        .insert(Player)
+        .id();
+
+    // light
+    commands.spawn_bundle(LightBundle {
+        transform: Transform::from_xyz(4.0, 8.0, 4.0),
+        ..Default::default()
+    });
+
+    commands.spawn_bundle(FollowCameraBundle::new(
+        FollowCameraController::default(),
+        FollowTarget::new(player, Vec3::new(0.0, 0.5, 0.0)),
+        PerspectiveCameraBundle::default(),
+        Vec3::new(5.0, 4.0, 6.0),
+        Vec3::new(5.0, 1.0, 0.0),
+        Vec3::Y,
+    ));
+}
+
+/// drive the cube around in a circle
+fn move_player_system(time: Res<Time>, mut players: Query<&mut Transform, With<Player>>) {
+    let angle = 0.5 * time.seconds_since_startup() as f32;
+    for mut transform in players.iter_mut() {
+        transform.translation = Vec3::new(5.0 * angle.cos(), 0.5, 5.0 * angle.sin());
+    }
+}
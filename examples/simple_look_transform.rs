@@ -39,10 +39,11 @@ fn setup(
 
     commands
         .spawn_bundle(LookTransformBundle {
-            transform: LookTransform {
-                eye: Vec3::new(-2.0, 2.5, 5.0),
-                target: Vec3::new(0.0, 0.5, 0.0),
-            },
+            transform: LookTransform::new(
+                Vec3::new(-2.0, 2.5, 5.0),
+                Vec3::new(0.0, 0.5, 0.0),
+                Vec3::Y,
+            ),
             smoother: Smoother::new(0.9),
         })
         .insert_bundle(PerspectiveCameraBundle {
@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+use smooth_bevy_cameras::{
+    controllers::ortho_2d::{Ortho2dCameraBundle, Ortho2dCameraController, Ortho2dCameraPlugin},
+    LookTransformPlugin,
+};
+
+fn main() {
+    App::build()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(LookTransformPlugin)
+        .add_plugin(Ortho2dCameraPlugin)
+        .add_startup_system(setup.system())
+        .run();
+}
+
+/// set up a simple 2D scene with a grid of squares
+fn setup(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    for x in -5..=5 {
+        for y in -5..=5 {
+            let color = if (x + y) % 2 == 0 {
+                Color::rgb(0.3, 0.5, 0.3)
+            } else {
+                Color::rgb(0.8, 0.7, 0.6)
+            };
+            commands.spawn_bundle(SpriteBundle {
+                material: materials.add(color.into()),
+                sprite: Sprite::new(Vec2::splat(90.0)),
+                transform: Transform::from_xyz(100.0 * x as f32, 100.0 * y as f32, 0.0),
+                ..Default::default()
+            });
+        }
+    }
+
+    commands.spawn_bundle(Ortho2dCameraBundle::new(
+        Ortho2dCameraController::default(),
+        OrthographicCameraBundle::new_2d(),
+        Vec2::ZERO,
+    ));
+}
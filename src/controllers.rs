@@ -1,3 +1,321 @@
+use crate::LookAngles;
+
+use bevy::{
+    app::prelude::*,
+    ecs::bundle::Bundle,
+    input::{
+        gamepad::{Gamepad, GamepadAxis, GamepadButton, GamepadEvent, GamepadEventType},
+        prelude::*,
+    },
+    math::prelude::*,
+    render::prelude::*,
+    transform::components::Transform,
+    utils::HashSet,
+};
+use serde::{Deserialize, Serialize};
+
+pub mod follow;
 pub mod fps;
+pub mod group;
 pub mod orbit;
+pub mod ortho_2d;
+pub mod rts;
+pub mod shoulder;
+pub mod trackball;
 pub mod unreal;
+
+/// A camera bundle that the controller bundles can be built with, i.e. `PerspectiveCameraBundle` or
+/// `OrthographicCameraBundle`.
+pub trait ProjectionBundle: Bundle {
+    fn transform_mut(&mut self) -> &mut Transform;
+}
+
+impl ProjectionBundle for PerspectiveCameraBundle {
+    fn transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+impl ProjectionBundle for OrthographicCameraBundle {
+    fn transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+/// A keyboard key or mouse button that triggers a controller action.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl InputBinding {
+    pub fn pressed(&self, keyboard: &Input<KeyCode>, mouse_buttons: &Input<MouseButton>) -> bool {
+        match *self {
+            Self::Key(key) => keyboard.pressed(key),
+            Self::Mouse(button) => mouse_buttons.pressed(button),
+        }
+    }
+
+    pub fn just_pressed(
+        &self,
+        keyboard: &Input<KeyCode>,
+        mouse_buttons: &Input<MouseButton>,
+    ) -> bool {
+        match *self {
+            Self::Key(key) => keyboard.just_pressed(key),
+            Self::Mouse(button) => mouse_buttons.just_pressed(button),
+        }
+    }
+}
+
+/// The set of connected gamepads, tracked from `GamepadEvent`s. Input maps keep one of these as a `Local`, and the sticks and
+/// triggers of all connected gamepads are summed together.
+#[derive(Default)]
+pub struct ConnectedGamepads {
+    gamepads: HashSet<Gamepad>,
+}
+
+impl ConnectedGamepads {
+    pub fn update(&mut self, events: &mut EventReader<GamepadEvent>) {
+        for GamepadEvent(gamepad, event_type) in events.iter() {
+            match event_type {
+                GamepadEventType::Connected => {
+                    self.gamepads.insert(*gamepad);
+                }
+                GamepadEventType::Disconnected => {
+                    self.gamepads.remove(gamepad);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    pub fn left_stick(&self, axes: &Axis<GamepadAxis>, dead_zone: f32) -> Vec2 {
+        self.stick(
+            axes,
+            GamepadAxisType::LeftStickX,
+            GamepadAxisType::LeftStickY,
+            dead_zone,
+        )
+    }
+
+    pub fn right_stick(&self, axes: &Axis<GamepadAxis>, dead_zone: f32) -> Vec2 {
+        self.stick(
+            axes,
+            GamepadAxisType::RightStickX,
+            GamepadAxisType::RightStickY,
+            dead_zone,
+        )
+    }
+
+    fn stick(
+        &self,
+        axes: &Axis<GamepadAxis>,
+        x_axis: GamepadAxisType,
+        y_axis: GamepadAxisType,
+        dead_zone: f32,
+    ) -> Vec2 {
+        let mut sum = Vec2::ZERO;
+        for &gamepad in self.gamepads.iter() {
+            let x = axes.get(GamepadAxis(gamepad, x_axis)).unwrap_or(0.0);
+            let y = axes.get(GamepadAxis(gamepad, y_axis)).unwrap_or(0.0);
+            sum += apply_radial_dead_zone(Vec2::new(x, y), dead_zone);
+        }
+
+        sum
+    }
+
+    /// How far the given analog button (e.g. `GamepadButtonType::RightTrigger2`) is pressed, between `0.0` and `1.0`.
+    pub fn trigger(
+        &self,
+        button_axes: &Axis<GamepadButton>,
+        button: GamepadButtonType,
+        dead_zone: f32,
+    ) -> f32 {
+        let mut sum = 0.0;
+        for &gamepad in self.gamepads.iter() {
+            let value = button_axes
+                .get(GamepadButton(gamepad, button))
+                .unwrap_or(0.0);
+            sum += apply_dead_zone(value, dead_zone);
+        }
+
+        sum
+    }
+}
+
+/// Computes the `LookAngles` of `direction` about `up`, recovering from a degenerate `LookTransform` (e.g. the eye is at the
+/// target) by keeping the orientation of the scene `Transform`, which is the last valid orientation that was rendered.
+/// `transform_direction` is the direction in the local space of the scene transform that corresponds to `direction`.
+pub(crate) fn look_angles_or_last_valid(
+    direction: Vec3,
+    up: Vec3,
+    scene_transform: &Transform,
+    transform_direction: Vec3,
+) -> LookAngles {
+    let last_direction = scene_transform.rotation * transform_direction;
+    let last_up = scene_transform.rotation * Vec3::Y;
+
+    LookAngles::from_vector_with_up(direction, up)
+        .or_else(|_| LookAngles::from_vector_with_up(last_direction, up))
+        .or_else(|_| LookAngles::from_vector_with_up(direction, last_up))
+        .or_else(|_| LookAngles::from_vector_with_up(last_direction, last_up))
+        .unwrap_or_default()
+}
+
+/// Replaces a degenerate radius between the eye and target with `1.0`, so it can be scaled again.
+pub(crate) fn nonzero_radius(radius: f32) -> f32 {
+    if radius > 0.0 && radius.is_finite() {
+        radius
+    } else {
+        1.0
+    }
+}
+
+/// Clamps the `radius` between the eye and target to a controller's `min` and `max`, where a `max` below the `min` is ignored. The
+/// result stays positive, so the eye never lands on the target.
+pub(crate) fn clamp_radius(radius: f32, min: f32, max: f32) -> f32 {
+    radius.max(min).min(max.max(min)).max(f32::EPSILON)
+}
+
+/// Never let the radius or orthographic scale get closer to zero than this, even if the controller allows it.
+pub(crate) const ABSOLUTE_MIN_ZOOM: f32 = 1e-4;
+
+/// With elastic zoom limits, the fraction of the zoom that still applies when zooming further past a limit.
+const ELASTIC_RESISTANCE: f32 = 0.25;
+
+/// With elastic zoom limits, the furthest the zoom can overshoot a limit, as a factor of the limit.
+const MAX_ELASTIC_OVERSHOOT: f32 = 2.0;
+
+/// Scales the `value` of a radius or orthographic scale by the zoom `scalar`, and applies the `min` and `max` limits. With an
+/// `elastic_half_life`, the value can overshoot the limits and springs back to them over time, where `dt` is the number of
+/// seconds since the last update.
+pub(crate) fn zoom_with_limits(
+    value: f32,
+    scalar: f32,
+    dt: f32,
+    min: f32,
+    max: f32,
+    elastic_half_life: Option<f32>,
+) -> f32 {
+    // Zooming is multiplicative, so limits are applied to the log of the value.
+    let log_min = min.max(ABSOLUTE_MIN_ZOOM).ln();
+    let log_max = max.max(ABSOLUTE_MIN_ZOOM).ln().max(log_min);
+    let log_value = value.max(ABSOLUTE_MIN_ZOOM).ln();
+    let mut log_delta = scalar.max(f32::EPSILON).ln();
+
+    let half_life = match elastic_half_life {
+        Some(half_life) if half_life > 0.0 => half_life,
+        _ => return (log_value + log_delta).max(log_min).min(log_max).exp(),
+    };
+
+    if (log_value >= log_max && log_delta > 0.0) || (log_value <= log_min && log_delta < 0.0) {
+        log_delta *= ELASTIC_RESISTANCE;
+    }
+    let mut new_log_value = log_value + log_delta;
+
+    // Spring back towards the limits.
+    let decay = 0.5f32.powf(dt / half_life);
+    if new_log_value > log_max {
+        new_log_value = log_max + (new_log_value - log_max) * decay;
+    } else if new_log_value < log_min {
+        new_log_value = log_min + (new_log_value - log_min) * decay;
+    }
+
+    let max_overshoot = MAX_ELASTIC_OVERSHOOT.ln();
+    new_log_value
+        .max(log_min - max_overshoot)
+        .min(log_max + max_overshoot)
+        .max(ABSOLUTE_MIN_ZOOM.ln())
+        .exp()
+}
+
+/// Zeroes any stick input whose magnitude is within `dead_zone`, and rescales the rest so the output still starts at zero.
+fn apply_radial_dead_zone(stick: Vec2, dead_zone: f32) -> Vec2 {
+    let magnitude = stick.length();
+    if magnitude <= dead_zone {
+        return Vec2::ZERO;
+    }
+
+    let rescaled = ((magnitude - dead_zone) / (1.0 - dead_zone)).min(1.0);
+
+    rescaled * stick / magnitude
+}
+
+fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    if value.abs() <= dead_zone {
+        return 0.0;
+    }
+
+    value.signum() * ((value.abs() - dead_zone) / (1.0 - dead_zone)).min(1.0)
+}
+
+// ████████╗███████╗███████╗████████╗
+// ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝
+//    ██║   █████╗  ███████╗   ██║
+//    ██║   ██╔══╝  ╚════██║   ██║
+//    ██║   ███████╗███████║   ██║
+//    ╚═╝   ╚══════╝╚══════╝   ╚═╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_look_angles_recover_from_eye_at_target() {
+        let scene_transform = Transform::from_xyz(0.0, 0.0, 0.0).looking_at(Vec3::X, Vec3::Y);
+        let eye = Vec3::new(1.0, 2.0, 3.0);
+        let degenerate_direction = (eye - eye).normalize();
+
+        let angles =
+            look_angles_or_last_valid(degenerate_direction, Vec3::Y, &scene_transform, -Vec3::Z);
+
+        let v = angles.unit_vector();
+        assert_relative_eq!(v.x, 1.0, epsilon = 1e-6);
+        assert_relative_eq!(v.y, 0.0, epsilon = 1e-6);
+        assert_relative_eq!(v.z, 0.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_look_angles_recover_from_zero_up() {
+        let scene_transform = Transform::default();
+
+        let angles = look_angles_or_last_valid(Vec3::X, Vec3::ZERO, &scene_transform, -Vec3::Z);
+
+        assert_relative_eq!(angles.up().y, 1.0);
+        assert_relative_eq!(angles.unit_vector().x, 1.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_clamp_radius() {
+        assert_relative_eq!(clamp_radius(0.5, 1.0, 10.0), 1.0);
+        assert_relative_eq!(clamp_radius(20.0, 1.0, 10.0), 10.0);
+        assert_relative_eq!(clamp_radius(5.0, 1.0, 10.0), 5.0);
+        // A max below the min is ignored.
+        assert_relative_eq!(clamp_radius(5.0, 2.0, 1.0), 2.0);
+        assert!(clamp_radius(0.0, 0.0, 10.0) > 0.0);
+    }
+
+    #[test]
+    fn test_radial_dead_zone() {
+        assert_eq!(apply_radial_dead_zone(Vec2::new(0.1, 0.1), 0.2), Vec2::ZERO);
+
+        let full = apply_radial_dead_zone(Vec2::new(1.0, 0.0), 0.2);
+        assert_relative_eq!(full.x, 1.0);
+        assert_relative_eq!(full.y, 0.0);
+
+        let half = apply_radial_dead_zone(Vec2::new(0.0, -0.6), 0.2);
+        assert_relative_eq!(half.x, 0.0);
+        assert_relative_eq!(half.y, -0.5);
+    }
+
+    #[test]
+    fn test_dead_zone() {
+        assert_eq!(apply_dead_zone(0.05, 0.1), 0.0);
+        assert_relative_eq!(apply_dead_zone(0.55, 0.1), 0.5);
+        assert_relative_eq!(apply_dead_zone(-1.0, 0.1), -1.0);
+    }
+}
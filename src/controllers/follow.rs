@@ -0,0 +1,500 @@
+use crate::{
+    controllers::{
+        clamp_radius, look_angles_or_last_valid, nonzero_radius, ConnectedGamepads, InputBinding,
+    },
+    AngleLimits, LookTransform, LookTransformBundle, Smoother, SmoothingMode,
+};
+
+use bevy::{
+    app::prelude::*,
+    core::Time,
+    ecs::{bundle::Bundle, prelude::*},
+    input::{
+        gamepad::{GamepadAxis, GamepadEvent},
+        mouse::{MouseMotion, MouseWheel},
+        prelude::*,
+    },
+    math::prelude::*,
+    render::prelude::*,
+    transform::components::{GlobalTransform, Transform},
+};
+use serde::{Deserialize, Serialize};
+
+pub struct FollowCameraPlugin;
+
+impl Plugin for FollowCameraPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<FollowCameraBindings>()
+            .add_system(default_input_map.system())
+            .add_system(control_system.system())
+            .add_event::<ControlEvent>();
+    }
+}
+
+#[derive(Bundle)]
+pub struct FollowCameraBundle {
+    controller: FollowCameraController,
+    follow_target: FollowTarget,
+    look_ahead: FollowLookAhead,
+    #[bundle]
+    look_transform: LookTransformBundle,
+    #[bundle]
+    perspective: PerspectiveCameraBundle,
+}
+
+impl FollowCameraBundle {
+    pub fn new(
+        controller: FollowCameraController,
+        follow_target: FollowTarget,
+        mut perspective: PerspectiveCameraBundle,
+        eye: Vec3,
+        target: Vec3,
+        up: Vec3,
+    ) -> Self {
+        // Make sure the transform is consistent with the controller to start.
+        perspective.transform = Transform::from_translation(eye).looking_at(target, up);
+
+        // Keep the smoothed eye on the orbit sphere, even when swinging far around the target.
+        let mut smoother = Smoother::new_separate(
+            controller.smoothing_weight,
+            controller.eye_smoothing_weight,
+            controller.target_smoothing_weight,
+        );
+        smoother.set_mode(SmoothingMode::Spherical);
+
+        Self {
+            controller,
+            follow_target,
+            look_ahead: FollowLookAhead::default(),
+            look_transform: LookTransformBundle {
+                transform: LookTransform::new(eye, target, up),
+                smoother,
+            },
+            perspective,
+        }
+    }
+}
+
+/// A 3rd person camera that keeps its target on a `FollowTarget` entity, while the player orbits around it.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct FollowCameraController {
+    pub enabled: bool,
+    /// Radians per pixel of mouse motion.
+    pub mouse_rotate_sensitivity: Vec2,
+    /// Fraction of the radius per line of mouse wheel scrolling.
+    pub mouse_wheel_zoom_sensitivity: f32,
+    /// Radians per second with the right stick fully tilted.
+    pub gamepad_rotate_sensitivity: Vec2,
+    /// Stick inputs below this magnitude are ignored.
+    pub gamepad_dead_zone: f32,
+    /// The closest the eye can get to the target.
+    pub min_radius: f32,
+    /// The furthest the eye can get from the target.
+    pub max_radius: f32,
+    /// Seconds of the followed entity's velocity to move the target ahead by, so the player sees where they are going.
+    /// `0.0` disables look-ahead.
+    pub look_ahead_time: f32,
+    /// The furthest the look-ahead can move the target, e.g. when the followed entity teleports.
+    pub max_look_ahead_distance: f32,
+    /// The half-life in seconds of the look-ahead smoothing, which is separate from the `Smoother` and evens out changes in
+    /// the estimated velocity.
+    pub look_ahead_half_life: f32,
+    /// Limits the angles of the eye as seen from the target. A positive pitch puts the eye above the target.
+    pub angle_limits: AngleLimits,
+    /// The `Smoother` lag weight of the eye and target, between `0.0` and `1.0`, where higher is smoother.
+    pub smoothing_weight: f32,
+    /// Overrides the `smoothing_weight` of the eye.
+    pub eye_smoothing_weight: Option<f32>,
+    /// Overrides the `smoothing_weight` of the target, which tracks the followed entity more tightly than the eye by default.
+    pub target_smoothing_weight: Option<f32>,
+}
+
+impl Default for FollowCameraController {
+    fn default() -> Self {
+        Self {
+            mouse_rotate_sensitivity: Vec2::splat(0.006),
+            mouse_wheel_zoom_sensitivity: 0.15,
+            gamepad_rotate_sensitivity: Vec2::splat(2.0),
+            gamepad_dead_zone: 0.15,
+            min_radius: 1.0,
+            max_radius: 50.0,
+            look_ahead_time: 0.0,
+            max_look_ahead_distance: 5.0,
+            look_ahead_half_life: 0.3,
+            angle_limits: AngleLimits::default(),
+            smoothing_weight: 0.8,
+            eye_smoothing_weight: None,
+            target_smoothing_weight: Some(0.6),
+            enabled: true,
+        }
+    }
+}
+
+/// The entity followed by a `FollowCameraController`. The camera's target is kept at the entity's `GlobalTransform`
+/// translation plus the `offset`, which is in world space, e.g. to look at a character's head instead of its feet.
+#[derive(Clone, Copy, Debug)]
+pub struct FollowTarget {
+    pub entity: Entity,
+    pub offset: Vec3,
+}
+
+impl FollowTarget {
+    pub fn new(entity: Entity, offset: Vec3) -> Self {
+        Self { entity, offset }
+    }
+}
+
+/// The look-ahead state of a `FollowCameraController`. The velocity of the followed entity is estimated from its positions in
+/// successive frames.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FollowLookAhead {
+    /// The followed entity's position in the last frame.
+    pub last_position: Option<Vec3>,
+    /// The smoothed offset that is currently added to the target.
+    pub offset: Vec3,
+}
+
+impl FollowLookAhead {
+    /// Updates the look-ahead for the followed entity at `position`, `dt` seconds after the last update, and returns the
+    /// offset to add to the target.
+    pub fn update(&mut self, position: Vec3, dt: f32, controller: &FollowCameraController) -> Vec3 {
+        let last_position = self.last_position.replace(position);
+        if dt <= 0.0 {
+            return self.offset;
+        }
+
+        let velocity = last_position.map_or(Vec3::ZERO, |last| (position - last) / dt);
+        let mut desired = controller.look_ahead_time.max(0.0) * velocity;
+        let max_distance = controller.max_look_ahead_distance.max(0.0);
+        if desired.length() > max_distance {
+            desired = max_distance * desired.normalize_or_zero();
+        }
+
+        let decay = if controller.look_ahead_half_life > 0.0 {
+            0.5f32.powf(dt / controller.look_ahead_half_life)
+        } else {
+            0.0
+        };
+        self.offset = desired + decay * (self.offset - desired);
+
+        self.offset
+    }
+}
+
+/// The inputs read by `follow::default_input_map`. Insert this resource before adding the `FollowCameraPlugin` to override
+/// the defaults, e.g. after deserializing it from a config file.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct FollowCameraBindings {
+    /// Held while dragging the mouse to orbit.
+    pub orbit: InputBinding,
+}
+
+impl Default for FollowCameraBindings {
+    fn default() -> Self {
+        Self {
+            orbit: InputBinding::Mouse(MouseButton::Right),
+        }
+    }
+}
+
+/// An input for the camera entity it is addressed to.
+pub enum ControlEvent {
+    Orbit(Entity, Vec2),
+    Zoom(Entity, f32),
+}
+
+impl ControlEvent {
+    /// The camera entity that should handle this event.
+    pub fn camera(&self) -> Entity {
+        match *self {
+            Self::Orbit(camera, _) | Self::Zoom(camera, _) => camera,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn default_input_map(
+    mut events: EventWriter<ControlEvent>,
+    time: Res<Time>,
+    bindings: Res<FollowCameraBindings>,
+    mut mouse_wheel_reader: EventReader<MouseWheel>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    keyboard: Res<Input<KeyCode>>,
+    mut gamepad_events: EventReader<GamepadEvent>,
+    mut gamepads: Local<ConnectedGamepads>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    controllers: Query<(Entity, &FollowCameraController)>,
+) {
+    let mut cursor_delta = Vec2::ZERO;
+    for event in mouse_motion_events.iter() {
+        cursor_delta += event.delta;
+    }
+
+    let wheel_deltas: Vec<f32> = mouse_wheel_reader.iter().map(|event| event.y).collect();
+
+    gamepads.update(&mut gamepad_events);
+
+    let dt = time.delta_seconds();
+
+    for (camera, controller) in controllers.iter() {
+        let FollowCameraController {
+            enabled,
+            mouse_rotate_sensitivity,
+            mouse_wheel_zoom_sensitivity,
+            gamepad_rotate_sensitivity,
+            gamepad_dead_zone,
+            ..
+        } = *controller;
+
+        if !enabled {
+            continue;
+        }
+
+        if bindings.orbit.pressed(&keyboard, &mouse_buttons) {
+            events.send(ControlEvent::Orbit(
+                camera,
+                mouse_rotate_sensitivity * cursor_delta,
+            ));
+        }
+
+        let mut scalar = 1.0;
+        for delta in wheel_deltas.iter() {
+            scalar *= 1.0 + -delta * mouse_wheel_zoom_sensitivity;
+        }
+        events.send(ControlEvent::Zoom(camera, scalar));
+
+        // The right stick acts like dragging the mouse in the direction of the stick.
+        let right_stick = gamepads.right_stick(&gamepad_axes, gamepad_dead_zone);
+        if right_stick != Vec2::ZERO {
+            events.send(ControlEvent::Orbit(
+                camera,
+                gamepad_rotate_sensitivity * dt * Vec2::new(right_stick.x, -right_stick.y),
+            ));
+        }
+    }
+}
+
+pub fn control_system(
+    mut events: EventReader<ControlEvent>,
+    time: Res<Time>,
+    mut cameras: Query<(
+        Entity,
+        &FollowCameraController,
+        &FollowTarget,
+        &mut FollowLookAhead,
+        &mut LookTransform,
+        &Transform,
+    )>,
+    followed: Query<&GlobalTransform>,
+) {
+    // Collect the events so each camera can pick out the ones addressed to it.
+    let events: Vec<&ControlEvent> = events.iter().collect();
+
+    for (camera, controller, follow_target, mut look_ahead, mut transform, scene_transform) in
+        cameras.iter_mut()
+    {
+        if !controller.enabled {
+            continue;
+        }
+
+        let mut look_angles = look_angles_or_last_valid(
+            -transform.look_direction(),
+            transform.up,
+            scene_transform,
+            Vec3::Z,
+        )
+        .with_limits(controller.angle_limits);
+        let mut radius_scalar = 1.0;
+
+        for event in events.iter().filter(|e| e.camera() == camera) {
+            match event {
+                ControlEvent::Orbit(_, delta) => {
+                    look_angles.add_yaw(-delta.x);
+                    look_angles.add_pitch(delta.y);
+                }
+                ControlEvent::Zoom(_, scalar) => {
+                    radius_scalar *= scalar;
+                }
+            }
+        }
+
+        // Measure the radius before moving the target, so the eye is carried along with the followed entity rather than
+        // having the entity walk into it.
+        let radius = clamp_radius(
+            nonzero_radius(transform.radius()) * radius_scalar,
+            controller.min_radius,
+            controller.max_radius,
+        );
+
+        // If the followed entity is gone, keep looking at where it was.
+        if let Ok(followed) = followed.get(follow_target.entity) {
+            let look_ahead_offset =
+                look_ahead.update(followed.translation, time.delta_seconds(), controller);
+            transform.target = followed.translation + follow_target.offset + look_ahead_offset;
+        }
+
+        transform.eye = transform.target + radius * look_angles.unit_vector();
+        transform.up = look_angles.up();
+    }
+}
+
+// ████████╗███████╗███████╗████████╗
+// ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝
+//    ██║   █████╗  ███████╗   ██║
+//    ██║   ██╔══╝  ╚════██║   ██║
+//    ██║   ███████╗███████║   ██║
+//    ╚═╝   ╚══════╝╚══════╝   ╚═╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+    use bevy::app::Events;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    /// A world with a followed entity at `position` and a camera following it, and a stage that runs the `control_system`.
+    fn follow_world(
+        controller: FollowCameraController,
+        position: Vec3,
+        offset: Vec3,
+        eye: Vec3,
+    ) -> (World, SystemStage, Entity, Entity) {
+        let mut world = World::default();
+        world.insert_resource(Time::default());
+        world.insert_resource(Events::<ControlEvent>::default());
+
+        let followed = world
+            .spawn()
+            .insert(GlobalTransform::from_translation(position))
+            .id();
+        let camera = world
+            .spawn()
+            .insert(controller)
+            .insert(FollowTarget::new(followed, offset))
+            .insert(FollowLookAhead::default())
+            .insert(LookTransform::new(eye, position + offset, Vec3::Y))
+            .insert(Transform::from_translation(eye).looking_at(position + offset, Vec3::Y))
+            .id();
+
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(control_system.system());
+
+        (world, stage, followed, camera)
+    }
+
+    fn look_transform(world: &World, camera: Entity) -> LookTransform {
+        *world.get::<LookTransform>(camera).unwrap()
+    }
+
+    #[test]
+    fn test_target_follows_entity_with_offset() {
+        let offset = Vec3::new(0.0, 1.0, 0.0);
+        let eye = Vec3::new(0.0, 3.0, 4.0);
+        let (mut world, mut stage, followed, camera) =
+            follow_world(FollowCameraController::default(), Vec3::ZERO, offset, eye);
+        let initial_eye_offset = eye - offset;
+
+        let position = Vec3::new(10.0, 0.0, -5.0);
+        *world.get_mut::<GlobalTransform>(followed).unwrap() =
+            GlobalTransform::from_translation(position);
+        stage.run(&mut world);
+
+        // The target moves with the entity, and the eye keeps its place relative to the target.
+        let transform = look_transform(&world, camera);
+        let eye_offset = transform.eye - transform.target;
+        assert_relative_eq!(transform.target.x, 10.0, epsilon = 1e-5);
+        assert_relative_eq!(transform.target.y, 1.0, epsilon = 1e-5);
+        assert_relative_eq!(transform.target.z, -5.0, epsilon = 1e-5);
+        assert_relative_eq!(eye_offset.x, initial_eye_offset.x, epsilon = 1e-4);
+        assert_relative_eq!(eye_offset.y, initial_eye_offset.y, epsilon = 1e-4);
+        assert_relative_eq!(eye_offset.z, initial_eye_offset.z, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_zoom_respects_radius_limits() {
+        let controller = FollowCameraController {
+            min_radius: 2.0,
+            max_radius: 8.0,
+            ..Default::default()
+        };
+        let (mut world, mut stage, _, camera) =
+            follow_world(controller, Vec3::ZERO, Vec3::ZERO, Vec3::new(0.0, 3.0, 4.0));
+
+        world
+            .get_resource_mut::<Events<ControlEvent>>()
+            .unwrap()
+            .send(ControlEvent::Zoom(camera, 0.01));
+        stage.run(&mut world);
+        assert_relative_eq!(look_transform(&world, camera).radius(), 2.0, epsilon = 1e-4);
+
+        world
+            .get_resource_mut::<Events<ControlEvent>>()
+            .unwrap()
+            .send(ControlEvent::Zoom(camera, 100.0));
+        stage.run(&mut world);
+        assert_relative_eq!(look_transform(&world, camera).radius(), 8.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_entity_moving_onto_the_eye_pushes_it_along() {
+        let eye = Vec3::new(0.0, 3.0, 4.0);
+        let (mut world, mut stage, followed, camera) = follow_world(
+            FollowCameraController::default(),
+            Vec3::ZERO,
+            Vec3::ZERO,
+            eye,
+        );
+
+        // Walk the entity straight towards the camera and through where the eye was.
+        for step in 1..=20 {
+            let position = eye * (step as f32 / 10.0);
+            *world.get_mut::<GlobalTransform>(followed).unwrap() =
+                GlobalTransform::from_translation(position);
+            stage.run(&mut world);
+
+            let transform = look_transform(&world, camera);
+            assert!(transform.has_orientation());
+            assert_relative_eq!(transform.radius(), 5.0, epsilon = 1e-3);
+            assert_relative_eq!(transform.eye.distance(position), 5.0, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_look_ahead_leads_constant_velocity() {
+        let controller = FollowCameraController {
+            look_ahead_time: 0.5,
+            ..Default::default()
+        };
+        let velocity = Vec3::new(4.0, 0.0, 0.0);
+
+        let mut look_ahead = FollowLookAhead::default();
+        let mut offset = Vec3::ZERO;
+        for i in 0..600 {
+            offset = look_ahead.update(i as f32 * DT * velocity, DT, &controller);
+        }
+
+        assert_relative_eq!(offset.x, 2.0, epsilon = 1e-3);
+        assert_relative_eq!(offset.y, 0.0);
+        assert_relative_eq!(offset.z, 0.0);
+    }
+
+    #[test]
+    fn test_look_ahead_is_limited_and_disabled_by_default() {
+        let mut look_ahead = FollowLookAhead::default();
+        let disabled = FollowCameraController::default();
+        look_ahead.update(Vec3::ZERO, DT, &disabled);
+        assert_eq!(look_ahead.update(Vec3::ONE, DT, &disabled), Vec3::ZERO);
+
+        // Teleporting doesn't throw the target across the map.
+        let controller = FollowCameraController {
+            look_ahead_time: 0.5,
+            look_ahead_half_life: 0.0,
+            ..Default::default()
+        };
+        let offset = look_ahead.update(Vec3::new(1000.0, 0.0, 0.0), DT, &controller);
+        assert_relative_eq!(offset.length(), controller.max_look_ahead_distance);
+    }
+}
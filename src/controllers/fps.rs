@@ -1,9 +1,20 @@
-use crate::{LookAngles, LookTransform, LookTransformBundle, Smoother};
+use crate::{
+    controllers::{
+        look_angles_or_last_valid, nonzero_radius, ConnectedGamepads, InputBinding,
+        ProjectionBundle,
+    },
+    AngleLimits, LookTransform, LookTransformBundle, Smoother,
+};
 
 use bevy::{
     app::prelude::*,
+    core::Time,
     ecs::{bundle::Bundle, prelude::*},
-    input::{mouse::MouseMotion, prelude::*},
+    input::{
+        gamepad::{GamepadAxis, GamepadButton, GamepadEvent},
+        mouse::MouseMotion,
+        prelude::*,
+    },
     math::prelude::*,
     render::prelude::*,
     transform::components::Transform,
@@ -14,38 +25,45 @@ pub struct FpsCameraPlugin;
 
 impl Plugin for FpsCameraPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_system(default_input_map.system())
+        app.init_resource::<FpsCameraBindings>()
+            .add_system(default_input_map.system())
             .add_system(control_system.system())
             .add_event::<ControlEvent>();
     }
 }
 
+/// Use a `PerspectiveCameraBundle` or an `OrthographicCameraBundle` for the projection.
 #[derive(Bundle)]
-pub struct FpsCameraBundle {
+pub struct FpsCameraBundle<P: ProjectionBundle = PerspectiveCameraBundle> {
     controller: FpsCameraController,
     #[bundle]
     look_transform: LookTransformBundle,
     #[bundle]
-    perspective: PerspectiveCameraBundle,
+    projection: P,
 }
 
-impl FpsCameraBundle {
+impl<P: ProjectionBundle> FpsCameraBundle<P> {
     pub fn new(
         controller: FpsCameraController,
-        mut perspective: PerspectiveCameraBundle,
+        mut projection: P,
         eye: Vec3,
         target: Vec3,
+        up: Vec3,
     ) -> Self {
         // Make sure the transform is consistent with the controller to start.
-        perspective.transform = Transform::from_translation(eye).looking_at(target, Vec3::Y);
+        *projection.transform_mut() = Transform::from_translation(eye).looking_at(target, up);
 
         Self {
             controller,
             look_transform: LookTransformBundle {
-                transform: LookTransform { eye, target },
-                smoother: Smoother::new(controller.smoothing_weight),
+                transform: LookTransform::new(eye, target, up),
+                smoother: Smoother::new_separate(
+                    controller.smoothing_weight,
+                    controller.eye_smoothing_weight,
+                    controller.target_smoothing_weight,
+                ),
             },
-            perspective,
+            projection,
         }
     }
 }
@@ -54,9 +72,24 @@ impl FpsCameraBundle {
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub struct FpsCameraController {
     pub enabled: bool,
+    /// Radians per pixel of mouse motion.
     pub mouse_rotate_sensitivity: Vec2,
+    /// Units per second while a movement key is held.
     pub translate_sensitivity: f32,
+    /// Radians per second with the right stick fully tilted.
+    pub gamepad_rotate_sensitivity: Vec2,
+    /// Units per second with the left stick (XZ) or a trigger (Y) fully pressed.
+    pub gamepad_translate_sensitivity: Vec3,
+    /// Stick and trigger inputs below this magnitude are ignored.
+    pub gamepad_dead_zone: f32,
+    /// Limits the angles of the look direction, relative to the up vector.
+    pub angle_limits: AngleLimits,
+    /// The `Smoother` lag weight of the eye and target, between `0.0` and `1.0`, where higher is smoother.
     pub smoothing_weight: f32,
+    /// Overrides the `smoothing_weight` of the eye.
+    pub eye_smoothing_weight: Option<f32>,
+    /// Overrides the `smoothing_weight` of the target.
+    pub target_smoothing_weight: Option<f32>,
 }
 
 impl Default for FpsCameraController {
@@ -64,105 +97,187 @@ impl Default for FpsCameraController {
         Self {
             enabled: true,
             mouse_rotate_sensitivity: Vec2::splat(0.002),
-            translate_sensitivity: 0.5,
+            translate_sensitivity: 30.0,
+            gamepad_rotate_sensitivity: Vec2::splat(2.0),
+            gamepad_translate_sensitivity: Vec3::splat(30.0),
+            gamepad_dead_zone: 0.15,
+            angle_limits: AngleLimits::default(),
             smoothing_weight: 0.9,
+            eye_smoothing_weight: None,
+            target_smoothing_weight: None,
+        }
+    }
+}
+
+/// The inputs read by `fps::default_input_map`. Insert this resource before adding the `FpsCameraPlugin` to override the
+/// defaults, e.g. after deserializing it from a config file.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct FpsCameraBindings {
+    pub forward: InputBinding,
+    pub left: InputBinding,
+    pub backward: InputBinding,
+    pub right: InputBinding,
+    pub down: InputBinding,
+    pub up: InputBinding,
+}
+
+impl Default for FpsCameraBindings {
+    fn default() -> Self {
+        Self {
+            forward: InputBinding::Key(KeyCode::W),
+            left: InputBinding::Key(KeyCode::A),
+            backward: InputBinding::Key(KeyCode::S),
+            right: InputBinding::Key(KeyCode::D),
+            down: InputBinding::Key(KeyCode::LShift),
+            up: InputBinding::Key(KeyCode::Space),
         }
     }
 }
 
+/// An input for the camera entity it is addressed to.
 pub enum ControlEvent {
-    Rotate(Vec2),
-    TranslateEye(Vec3),
+    Rotate(Entity, Vec2),
+    TranslateEye(Entity, Vec3),
+}
+
+impl ControlEvent {
+    /// The camera entity that should handle this event.
+    pub fn camera(&self) -> Entity {
+        match *self {
+            Self::Rotate(camera, _) | Self::TranslateEye(camera, _) => camera,
+        }
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn default_input_map(
     mut events: EventWriter<ControlEvent>,
+    time: Res<Time>,
+    bindings: Res<FpsCameraBindings>,
     keyboard: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
     mut mouse_motion_events: EventReader<MouseMotion>,
-    controllers: Query<&FpsCameraController>,
+    mut gamepad_events: EventReader<GamepadEvent>,
+    mut gamepads: Local<ConnectedGamepads>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepad_button_axes: Res<Axis<GamepadButton>>,
+    controllers: Query<(Entity, &FpsCameraController)>,
 ) {
-    // Can only control one camera at a time.
-    let controller = if let Some(controller) = controllers.iter().next() {
-        controller
-    } else {
-        return;
-    };
-    let FpsCameraController {
-        enabled,
-        translate_sensitivity,
-        mouse_rotate_sensitivity,
-        ..
-    } = *controller;
-
-    if !enabled {
-        return;
-    }
-
     let mut cursor_delta = Vec2::ZERO;
     for event in mouse_motion_events.iter() {
         cursor_delta += event.delta;
     }
 
-    events.send(ControlEvent::Rotate(
-        mouse_rotate_sensitivity * cursor_delta,
-    ));
-
-    for (key, dir) in [
-        (KeyCode::W, Vec3::Z),
-        (KeyCode::A, Vec3::X),
-        (KeyCode::S, -Vec3::Z),
-        (KeyCode::D, -Vec3::X),
-        (KeyCode::LShift, -Vec3::Y),
-        (KeyCode::Space, Vec3::Y),
-    ]
-    .iter()
-    .cloned()
-    {
-        if keyboard.pressed(key) {
-            events.send(ControlEvent::TranslateEye(translate_sensitivity * dir));
+    gamepads.update(&mut gamepad_events);
+
+    let dt = time.delta_seconds();
+
+    for (camera, controller) in controllers.iter() {
+        let FpsCameraController {
+            enabled,
+            translate_sensitivity,
+            mouse_rotate_sensitivity,
+            gamepad_rotate_sensitivity,
+            gamepad_translate_sensitivity,
+            gamepad_dead_zone,
+            ..
+        } = *controller;
+
+        if !enabled {
+            continue;
+        }
+
+        events.send(ControlEvent::Rotate(
+            camera,
+            mouse_rotate_sensitivity * cursor_delta,
+        ));
+
+        for (binding, dir) in [
+            (bindings.forward, Vec3::Z),
+            (bindings.left, Vec3::X),
+            (bindings.backward, -Vec3::Z),
+            (bindings.right, -Vec3::X),
+            (bindings.down, -Vec3::Y),
+            (bindings.up, Vec3::Y),
+        ]
+        .iter()
+        .cloned()
+        {
+            if binding.pressed(&keyboard, &mouse_buttons) {
+                events.send(ControlEvent::TranslateEye(
+                    camera,
+                    translate_sensitivity * dt * dir,
+                ));
+            }
+        }
+
+        let right_stick = gamepads.right_stick(&gamepad_axes, gamepad_dead_zone);
+        if right_stick != Vec2::ZERO {
+            // Pushing the stick up should look up, like moving the mouse up.
+            events.send(ControlEvent::Rotate(
+                camera,
+                gamepad_rotate_sensitivity * dt * Vec2::new(right_stick.x, -right_stick.y),
+            ));
+        }
+
+        let left_stick = gamepads.left_stick(&gamepad_axes, gamepad_dead_zone);
+        let vertical = gamepads.trigger(
+            &gamepad_button_axes,
+            GamepadButtonType::RightTrigger2,
+            gamepad_dead_zone,
+        ) - gamepads.trigger(
+            &gamepad_button_axes,
+            GamepadButtonType::LeftTrigger2,
+            gamepad_dead_zone,
+        );
+        let gamepad_dir = Vec3::new(-left_stick.x, vertical, left_stick.y);
+        if gamepad_dir != Vec3::ZERO {
+            events.send(ControlEvent::TranslateEye(
+                camera,
+                gamepad_translate_sensitivity * dt * gamepad_dir,
+            ));
         }
     }
 }
 
 pub fn control_system(
     mut events: EventReader<ControlEvent>,
-    mut cameras: Query<(&FpsCameraController, &mut LookTransform)>,
+    mut cameras: Query<(Entity, &FpsCameraController, &mut LookTransform, &Transform)>,
 ) {
-    // Can only control one camera at a time.
-    let (controller, mut transform) =
-        if let Some((controller, transform)) = cameras.iter_mut().next() {
-            (controller, transform)
-        } else {
-            return;
-        };
-
-    if controller.enabled {
+    // Collect the events so each camera can pick out the ones addressed to it.
+    let events: Vec<&ControlEvent> = events.iter().collect();
+
+    for (camera, controller, mut transform, scene_transform) in cameras.iter_mut() {
+        if !controller.enabled {
+            continue;
+        }
+
         let look_vector = transform.look_direction();
-        let mut look_angles = LookAngles::from_vector(look_vector);
+        let mut look_angles =
+            look_angles_or_last_valid(look_vector, transform.up, scene_transform, -Vec3::Z)
+                .with_limits(controller.angle_limits);
 
-        let yaw_rot = Quat::from_axis_angle(Vec3::Y, look_angles.get_yaw());
+        let yaw_rot = look_angles.yaw_rotation();
         let rot_x = yaw_rot * Vec3::X;
         let rot_y = yaw_rot * Vec3::Y;
         let rot_z = yaw_rot * Vec3::Z;
 
-        for event in events.iter() {
+        for event in events.iter().filter(|e| e.camera() == camera) {
             match event {
-                ControlEvent::Rotate(delta) => {
+                ControlEvent::Rotate(_, delta) => {
                     // Rotates with pitch and yaw.
                     look_angles.add_yaw(-delta.x);
                     look_angles.add_pitch(-delta.y);
                 }
-                ControlEvent::TranslateEye(delta) => {
-                    // Translates up/down (Y) left/right (X) and forward/back (Z).
+                ControlEvent::TranslateEye(_, delta) => {
+                    // Translates up/down (Y) left/right (X) and forward/back (Z), relative to the up axis.
                     transform.eye += delta.x * rot_x + delta.y * rot_y + delta.z * rot_z;
                 }
             }
         }
 
-        look_angles.assert_not_looking_up();
-
-        transform.target = transform.eye + transform.radius() * look_angles.unit_vector();
-    } else {
-        events.iter(); // Drop the events.
+        transform.target =
+            transform.eye + nonzero_radius(transform.radius()) * look_angles.unit_vector();
+        transform.up = look_angles.up();
     }
 }
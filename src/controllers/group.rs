@@ -0,0 +1,217 @@
+use crate::{frame_perspective, Bounds, LookTransform, LookTransformBundle, Smoother};
+
+use bevy::{
+    app::prelude::*,
+    ecs::{bundle::Bundle, prelude::*},
+    math::prelude::*,
+    render::{camera::PerspectiveProjection, prelude::*},
+    transform::components::{GlobalTransform, Transform},
+};
+use serde::{Deserialize, Serialize};
+
+pub struct GroupCameraPlugin;
+
+impl Plugin for GroupCameraPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system(control_system.system());
+    }
+}
+
+#[derive(Bundle)]
+pub struct GroupCameraBundle {
+    controller: GroupCameraController,
+    targets: GroupCameraTargets,
+    #[bundle]
+    look_transform: LookTransformBundle,
+    #[bundle]
+    perspective: PerspectiveCameraBundle,
+}
+
+impl GroupCameraBundle {
+    pub fn new(
+        controller: GroupCameraController,
+        targets: GroupCameraTargets,
+        mut perspective: PerspectiveCameraBundle,
+        target: Vec3,
+        up: Vec3,
+    ) -> Self {
+        let eye = target - controller.min_distance * view_direction(&controller);
+
+        // Make sure the transform is consistent with the controller to start.
+        perspective.transform = Transform::from_translation(eye).looking_at(target, up);
+
+        Self {
+            controller,
+            targets,
+            look_transform: LookTransformBundle {
+                transform: LookTransform::new(eye, target, up),
+                smoother: Smoother::new_separate(
+                    controller.smoothing_weight,
+                    controller.eye_smoothing_weight,
+                    controller.target_smoothing_weight,
+                ),
+            },
+            perspective,
+        }
+    }
+}
+
+/// A camera that keeps a group of entities in view, like in a local multiplayer game. It looks in a fixed direction and moves
+/// so the bounding sphere of the `GroupCameraTargets` fits in the view.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct GroupCameraController {
+    pub enabled: bool,
+    /// The direction from the eye to the target. It must not be parallel to the up vector.
+    pub view_direction: Vec3,
+    /// Extra space around the entities, as a fraction of the radius of their bounds.
+    pub padding: f32,
+    /// The closest the eye can get to the center of the group, e.g. when all entities are in the same place.
+    pub min_distance: f32,
+    /// The furthest the eye can get from the center of the group, even if some entities leave the view.
+    pub max_distance: f32,
+    /// The `Smoother` lag weight of the eye and target, between `0.0` and `1.0`, where higher is smoother.
+    pub smoothing_weight: f32,
+    /// Overrides the `smoothing_weight` of the eye.
+    pub eye_smoothing_weight: Option<f32>,
+    /// Overrides the `smoothing_weight` of the target.
+    pub target_smoothing_weight: Option<f32>,
+}
+
+impl Default for GroupCameraController {
+    fn default() -> Self {
+        Self {
+            view_direction: Vec3::new(0.0, -1.0, -1.0),
+            padding: 0.2,
+            min_distance: 5.0,
+            max_distance: 100.0,
+            smoothing_weight: 0.9,
+            eye_smoothing_weight: None,
+            target_smoothing_weight: None,
+            enabled: true,
+        }
+    }
+}
+
+/// The entities kept in view by a `GroupCameraController`. Entities without a `GlobalTransform` are ignored.
+#[derive(Clone, Debug, Default)]
+pub struct GroupCameraTargets(pub Vec<Entity>);
+
+pub fn control_system(
+    mut cameras: Query<(
+        &GroupCameraController,
+        &GroupCameraTargets,
+        &mut LookTransform,
+        &PerspectiveProjection,
+    )>,
+    targets: Query<&GlobalTransform>,
+) {
+    for (controller, group, mut transform, projection) in cameras.iter_mut() {
+        if !controller.enabled {
+            continue;
+        }
+
+        let points = group
+            .0
+            .iter()
+            .filter_map(|&entity| targets.get(entity).ok())
+            .map(|target| target.translation);
+        if let Some(framed) = frame_group(points, controller, projection, transform.up) {
+            *transform = framed;
+        }
+    }
+}
+
+/// The `LookTransform` that fits all of the `points` in the view, or `None` if there are no points.
+fn frame_group(
+    points: impl IntoIterator<Item = Vec3>,
+    controller: &GroupCameraController,
+    projection: &PerspectiveProjection,
+    up: Vec3,
+) -> Option<LookTransform> {
+    let bounds = Bounds::from_points(points)?;
+
+    let direction = view_direction(controller);
+    let view = LookTransform::new(-direction, Vec3::ZERO, up);
+
+    let framed = frame_perspective(
+        &view,
+        &bounds,
+        controller.padding,
+        projection.fov,
+        projection.aspect_ratio,
+    );
+    let distance = framed
+        .radius()
+        .max(controller.min_distance)
+        .min(controller.max_distance.max(controller.min_distance));
+
+    Some(LookTransform::new(
+        framed.target - distance * direction,
+        framed.target,
+        up,
+    ))
+}
+
+fn view_direction(controller: &GroupCameraController) -> Vec3 {
+    let direction = controller.view_direction.normalize_or_zero();
+    if direction == Vec3::ZERO {
+        -Vec3::Z
+    } else {
+        direction
+    }
+}
+
+// ████████╗███████╗███████╗████████╗
+// ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝
+//    ██║   █████╗  ███████╗   ██║
+//    ██║   ██╔══╝  ╚════██║   ██║
+//    ██║   ███████╗███████║   ██║
+//    ╚═╝   ╚══════╝╚══════╝   ╚═╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_frame_group_centers_on_entities() {
+        let controller = GroupCameraController::default();
+        let framed = frame_group(
+            vec![Vec3::new(-10.0, 0.0, 0.0), Vec3::new(10.0, 0.0, 4.0)],
+            &controller,
+            &PerspectiveProjection::default(),
+            Vec3::Y,
+        )
+        .unwrap();
+
+        assert_eq!(framed.target, Vec3::new(0.0, 0.0, 2.0));
+        let direction = controller.view_direction.normalize();
+        assert_relative_eq!(framed.look_direction().dot(direction), 1.0, epsilon = 1e-5);
+        assert!(framed.radius() > controller.min_distance);
+    }
+
+    #[test]
+    fn test_frame_group_distance_limits() {
+        let controller = GroupCameraController {
+            min_distance: 5.0,
+            max_distance: 20.0,
+            ..Default::default()
+        };
+        let projection = PerspectiveProjection::default();
+
+        let together = frame_group(vec![Vec3::ONE; 2], &controller, &projection, Vec3::Y).unwrap();
+        assert_relative_eq!(together.radius(), 5.0, epsilon = 1e-4);
+
+        let apart = frame_group(
+            vec![Vec3::new(-100.0, 0.0, 0.0), Vec3::new(100.0, 0.0, 0.0)],
+            &controller,
+            &projection,
+            Vec3::Y,
+        )
+        .unwrap();
+        assert_relative_eq!(apart.radius(), 20.0, epsilon = 1e-4);
+
+        assert!(frame_group(Vec::new(), &controller, &projection, Vec3::Y).is_none());
+    }
+}
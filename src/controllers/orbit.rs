@@ -1,15 +1,30 @@
-use crate::{LookAngles, LookTransform, LookTransformBundle, Smoother};
+use crate::{
+    controllers::{
+        look_angles_or_last_valid, nonzero_radius, zoom_with_limits, ConnectedGamepads,
+        InputBinding, ProjectionBundle, ABSOLUTE_MIN_ZOOM,
+    },
+    AngleLimits, LookTransform, LookTransformBundle, PlanePicker, PointPicker, Ray3d, Smoother,
+    SmoothingMode,
+};
 
 use bevy::{
     app::prelude::*,
+    core::Time,
     ecs::{bundle::Bundle, prelude::*},
     input::{
+        gamepad::{GamepadAxis, GamepadButton, GamepadEvent},
         mouse::{MouseMotion, MouseWheel},
         prelude::*,
+        touch::Touches,
     },
     math::prelude::*,
-    render::prelude::*,
+    render::{
+        camera::{Camera, CameraProjection, OrthographicProjection, PerspectiveProjection},
+        prelude::*,
+    },
     transform::components::Transform,
+    utils::HashMap,
+    window::Windows,
 };
 use serde::{Deserialize, Serialize};
 
@@ -17,38 +32,52 @@ pub struct OrbitCameraPlugin;
 
 impl Plugin for OrbitCameraPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_system(default_input_map.system())
+        app.init_resource::<OrbitCameraBindings>()
+            .init_resource::<OrbitPivotPicker>()
+            .add_system(default_input_map.system())
+            .add_system(touch_input_map.system())
+            .add_system(auto_rotate_system.system())
             .add_system(control_system.system())
             .add_event::<ControlEvent>();
     }
 }
 
+/// Use a `PerspectiveCameraBundle` or an `OrthographicCameraBundle` for the projection.
 #[derive(Bundle)]
-pub struct OrbitCameraBundle {
+pub struct OrbitCameraBundle<P: ProjectionBundle = PerspectiveCameraBundle> {
     controller: OrbitCameraController,
     #[bundle]
     look_transform: LookTransformBundle,
     #[bundle]
-    perspective: PerspectiveCameraBundle,
+    projection: P,
 }
 
-impl OrbitCameraBundle {
+impl<P: ProjectionBundle> OrbitCameraBundle<P> {
     pub fn new(
         controller: OrbitCameraController,
-        mut perspective: PerspectiveCameraBundle,
+        mut projection: P,
         eye: Vec3,
         target: Vec3,
+        up: Vec3,
     ) -> Self {
         // Make sure the transform is consistent with the controller to start.
-        perspective.transform = Transform::from_translation(eye).looking_at(target, Vec3::Y);
+        *projection.transform_mut() = Transform::from_translation(eye).looking_at(target, up);
+
+        // Keep the smoothed eye on the orbit sphere, even when swinging far around the target.
+        let mut smoother = Smoother::new_separate(
+            controller.smoothing_weight,
+            controller.eye_smoothing_weight,
+            controller.target_smoothing_weight,
+        );
+        smoother.set_mode(SmoothingMode::Spherical);
 
         Self {
             controller,
             look_transform: LookTransformBundle {
-                transform: LookTransform { eye, target },
-                smoother: Smoother::new(controller.smoothing_weight),
+                transform: LookTransform::new(eye, target, up),
+                smoother,
             },
-            perspective,
+            projection,
         }
     }
 }
@@ -57,10 +86,58 @@ impl OrbitCameraBundle {
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub struct OrbitCameraController {
     pub enabled: bool,
+    /// Radians per pixel of mouse motion.
     pub mouse_rotate_sensitivity: Vec2,
+    /// Units per pixel of mouse motion.
     pub mouse_translate_sensitivity: Vec2,
+    /// Fraction of the radius per line of mouse wheel scrolling.
     pub mouse_wheel_zoom_sensitivity: f32,
+    /// If set, the mouse wheel zooms toward the point under the cursor instead of the target, and the target moves along with
+    /// the eye.
+    pub zoom_to_cursor: bool,
+    /// If set, pressing the orbit binding picks the point under the cursor with the `OrbitPivotPicker`, and the camera orbits
+    /// around that point instead of the target until the binding is released.
+    pub pivot_on_cursor: bool,
+    /// Radians per second with the right stick fully tilted.
+    pub gamepad_rotate_sensitivity: Vec2,
+    /// Units per second with the left stick fully tilted.
+    pub gamepad_translate_sensitivity: Vec2,
+    /// Fraction of the radius per second with a trigger fully pressed.
+    pub gamepad_zoom_sensitivity: f32,
+    /// Stick and trigger inputs below this magnitude are ignored.
+    pub gamepad_dead_zone: f32,
+    /// Radians per pixel of one-finger drag.
+    pub touch_rotate_sensitivity: Vec2,
+    /// Units per pixel of two-finger drag.
+    pub touch_translate_sensitivity: Vec2,
+    /// Exponent applied to the pinch ratio. At `1.0`, pinching to half the finger distance doubles the radius.
+    pub touch_zoom_sensitivity: f32,
+    /// The closest the eye can get to the target.
+    pub min_radius: f32,
+    /// The furthest the eye can get from the target.
+    pub max_radius: f32,
+    /// The smallest scale of an `OrthographicProjection`, which is zoomed instead of the radius.
+    pub min_orthographic_scale: f32,
+    /// The largest scale of an `OrthographicProjection`, which is zoomed instead of the radius.
+    pub max_orthographic_scale: f32,
+    /// If set, zooming past the radius or scale limits is resisted instead of stopped, and the zoom springs back to the limit
+    /// with this half-life in seconds.
+    pub elastic_radius_half_life: Option<f32>,
+    /// Limits the angles of the eye as seen from the target. A positive pitch puts the eye above the target.
+    pub angle_limits: AngleLimits,
+    /// If set, the camera starts turning around its target like a turntable after this many seconds without keyboard, mouse,
+    /// touch or gamepad input, and stops as soon as there is input again. See `auto_rotate_system`.
+    pub auto_rotate_delay: Option<f32>,
+    /// Radians per second of auto-rotation.
+    pub auto_rotate_speed: f32,
+    /// The way the scene appears to turn during auto-rotation, as seen from above the target.
+    pub auto_rotate_direction: RotationDirection,
+    /// The `Smoother` lag weight of the eye and target, between `0.0` and `1.0`, where higher is smoother.
     pub smoothing_weight: f32,
+    /// Overrides the `smoothing_weight` of the eye.
+    pub eye_smoothing_weight: Option<f32>,
+    /// Overrides the `smoothing_weight` of the target.
+    pub target_smoothing_weight: Option<f32>,
 }
 
 impl Default for OrbitCameraController {
@@ -69,104 +146,904 @@ impl Default for OrbitCameraController {
             mouse_rotate_sensitivity: Vec2::splat(0.006),
             mouse_translate_sensitivity: Vec2::splat(0.008),
             mouse_wheel_zoom_sensitivity: 0.15,
+            zoom_to_cursor: false,
+            pivot_on_cursor: false,
+            gamepad_rotate_sensitivity: Vec2::splat(2.0),
+            gamepad_translate_sensitivity: Vec2::splat(4.0),
+            gamepad_zoom_sensitivity: 1.0,
+            gamepad_dead_zone: 0.15,
+            touch_rotate_sensitivity: Vec2::splat(0.006),
+            touch_translate_sensitivity: Vec2::splat(0.008),
+            touch_zoom_sensitivity: 1.0,
+            min_radius: 0.1,
+            max_radius: 1000.0,
+            min_orthographic_scale: 1e-3,
+            max_orthographic_scale: 100.0,
+            elastic_radius_half_life: None,
+            angle_limits: AngleLimits::default(),
+            auto_rotate_delay: None,
+            auto_rotate_speed: 0.3,
+            auto_rotate_direction: RotationDirection::Counterclockwise,
             smoothing_weight: 0.8,
+            eye_smoothing_weight: None,
+            target_smoothing_weight: None,
             enabled: true,
         }
     }
 }
 
+/// A direction of rotation around the up axis, as seen from above.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum RotationDirection {
+    Clockwise,
+    Counterclockwise,
+}
+
+/// The inputs read by `orbit::default_input_map`. Insert this resource before adding the `OrbitCameraPlugin` to override the
+/// defaults, e.g. after deserializing it from a config file.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct OrbitCameraBindings {
+    /// Held while dragging the mouse to orbit.
+    pub orbit: InputBinding,
+    /// Held while dragging the mouse to pan.
+    pub pan: InputBinding,
+}
+
+impl Default for OrbitCameraBindings {
+    fn default() -> Self {
+        Self {
+            orbit: InputBinding::Key(KeyCode::LControl),
+            pan: InputBinding::Mouse(MouseButton::Right),
+        }
+    }
+}
+
+/// Picks the pivot for `OrbitCameraController::pivot_on_cursor`. Defaults to a `PlanePicker` on the XZ plane, which is only the
+/// ground in a Y-up scene; for a Z-up scene, insert `OrbitPivotPicker(Box::new(PlanePicker::ground(Vec3::Z)))`. Insert your
+/// own to pick against the scene. Picking usually needs data from the ECS world, so keep the picker up to date from a system.
+pub struct OrbitPivotPicker(pub Box<dyn PointPicker>);
+
+impl Default for OrbitPivotPicker {
+    fn default() -> Self {
+        Self(Box::new(PlanePicker::default()))
+    }
+}
+
+/// An input for the camera entity it is addressed to.
 pub enum ControlEvent {
-    Orbit(Vec2),
-    TranslateTarget(Vec2),
-    Zoom(f32),
+    Orbit(Entity, Vec2),
+    /// Like `Orbit`, but rotates both the eye and the target around the given point.
+    OrbitAround(Entity, Vec2, Vec3),
+    TranslateTarget(Entity, Vec2),
+    Zoom(Entity, f32),
+    /// Like `Zoom`, but scales the distance of both the eye and the target from the given point.
+    ZoomToPoint(Entity, f32, Vec3),
+}
+
+impl ControlEvent {
+    /// The camera entity that should handle this event.
+    pub fn camera(&self) -> Entity {
+        match *self {
+            Self::Orbit(camera, _)
+            | Self::OrbitAround(camera, _, _)
+            | Self::TranslateTarget(camera, _)
+            | Self::Zoom(camera, _)
+            | Self::ZoomToPoint(camera, _, _) => camera,
+        }
+    }
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn default_input_map(
     mut events: EventWriter<ControlEvent>,
+    time: Res<Time>,
+    bindings: Res<OrbitCameraBindings>,
     mut mouse_wheel_reader: EventReader<MouseWheel>,
     mut mouse_motion_events: EventReader<MouseMotion>,
     mouse_buttons: Res<Input<MouseButton>>,
     keyboard: Res<Input<KeyCode>>,
-    controllers: Query<&OrbitCameraController>,
+    mut gamepad_events: EventReader<GamepadEvent>,
+    mut gamepads: Local<ConnectedGamepads>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepad_button_axes: Res<Axis<GamepadButton>>,
+    windows: Res<Windows>,
+    picker: Res<OrbitPivotPicker>,
+    mut pivots: Local<HashMap<Entity, Vec3>>,
+    controllers: Query<(
+        Entity,
+        &OrbitCameraController,
+        &LookTransform,
+        &Transform,
+        Option<&PerspectiveProjection>,
+        Option<&OrthographicProjection>,
+    )>,
 ) {
-    // Can only control one camera at a time.
-    let controller = if let Some(controller) = controllers.iter().next() {
-        controller
-    } else {
-        return;
-    };
-    let OrbitCameraController {
-        enabled,
-        mouse_rotate_sensitivity,
-        mouse_translate_sensitivity,
-        mouse_wheel_zoom_sensitivity,
-        ..
-    } = *controller;
-
-    if !enabled {
-        return;
-    }
-
     let mut cursor_delta = Vec2::ZERO;
     for event in mouse_motion_events.iter() {
         cursor_delta += event.delta;
     }
 
-    if keyboard.pressed(KeyCode::LControl) {
-        events.send(ControlEvent::Orbit(mouse_rotate_sensitivity * cursor_delta));
+    let wheel_deltas: Vec<f32> = mouse_wheel_reader.iter().map(|event| event.y).collect();
+
+    gamepads.update(&mut gamepad_events);
+
+    let dt = time.delta_seconds();
+
+    for (camera, controller, transform, scene_transform, perspective, orthographic) in
+        controllers.iter()
+    {
+        let OrbitCameraController {
+            enabled,
+            mouse_rotate_sensitivity,
+            mouse_translate_sensitivity,
+            mouse_wheel_zoom_sensitivity,
+            zoom_to_cursor,
+            pivot_on_cursor,
+            gamepad_rotate_sensitivity,
+            gamepad_translate_sensitivity,
+            gamepad_zoom_sensitivity,
+            gamepad_dead_zone,
+            ..
+        } = *controller;
+
+        if !enabled {
+            pivots.remove(&camera);
+            continue;
+        }
+
+        // The pivot is picked once when the binding is pressed, so it doesn't move while orbiting.
+        if !pivot_on_cursor || !bindings.orbit.pressed(&keyboard, &mouse_buttons) {
+            pivots.remove(&camera);
+        } else if bindings.orbit.just_pressed(&keyboard, &mouse_buttons) {
+            let pivot = cursor_ray(&windows, perspective, orthographic, scene_transform)
+                .and_then(|ray| picker.0.pick(&ray));
+            if let Some(pivot) = pivot {
+                pivots.insert(camera, pivot);
+            }
+        }
+
+        if bindings.orbit.pressed(&keyboard, &mouse_buttons) {
+            let delta = mouse_rotate_sensitivity * cursor_delta;
+            if let Some(&pivot) = pivots.get(&camera) {
+                events.send(ControlEvent::OrbitAround(camera, delta, pivot));
+            } else {
+                events.send(ControlEvent::Orbit(camera, delta));
+            }
+        }
+
+        if bindings.pan.pressed(&keyboard, &mouse_buttons) {
+            events.send(ControlEvent::TranslateTarget(
+                camera,
+                mouse_translate_sensitivity * cursor_delta,
+            ));
+        }
+
+        let mut scalar = 1.0;
+        for delta in wheel_deltas.iter() {
+            scalar *= 1.0 + -delta * mouse_wheel_zoom_sensitivity;
+        }
+        let zoom_point = if zoom_to_cursor && !wheel_deltas.is_empty() {
+            cursor_ray(&windows, perspective, orthographic, scene_transform)
+                .and_then(|ray| point_on_target_plane(&ray, transform))
+        } else {
+            None
+        };
+        if let Some(point) = zoom_point {
+            events.send(ControlEvent::ZoomToPoint(camera, scalar, point));
+        } else {
+            events.send(ControlEvent::Zoom(camera, scalar));
+        }
+
+        // Sticks are mapped to act like dragging the mouse in the direction of the stick.
+        let right_stick = gamepads.right_stick(&gamepad_axes, gamepad_dead_zone);
+        if right_stick != Vec2::ZERO {
+            events.send(ControlEvent::Orbit(
+                camera,
+                gamepad_rotate_sensitivity * dt * Vec2::new(right_stick.x, -right_stick.y),
+            ));
+        }
+
+        let left_stick = gamepads.left_stick(&gamepad_axes, gamepad_dead_zone);
+        if left_stick != Vec2::ZERO {
+            events.send(ControlEvent::TranslateTarget(
+                camera,
+                gamepad_translate_sensitivity * dt * Vec2::new(-left_stick.x, left_stick.y),
+            ));
+        }
+
+        let zoom_in = gamepads.trigger(
+            &gamepad_button_axes,
+            GamepadButtonType::RightTrigger2,
+            gamepad_dead_zone,
+        ) - gamepads.trigger(
+            &gamepad_button_axes,
+            GamepadButtonType::LeftTrigger2,
+            gamepad_dead_zone,
+        );
+        if zoom_in != 0.0 {
+            events.send(ControlEvent::Zoom(
+                camera,
+                1.0 - zoom_in * gamepad_zoom_sensitivity * dt,
+            ));
+        }
+    }
+}
+
+/// Orbits each camera with an `auto_rotate_delay` like a turntable, once there has been no input for that long. Any input
+/// counts, even if it doesn't move the camera, like a key that isn't bound.
+#[allow(clippy::too_many_arguments)]
+pub fn auto_rotate_system(
+    mut events: EventWriter<ControlEvent>,
+    time: Res<Time>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    mut mouse_wheel_reader: EventReader<MouseWheel>,
+    mut gamepad_events: EventReader<GamepadEvent>,
+    mut gamepads: Local<ConnectedGamepads>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepad_button_axes: Res<Axis<GamepadButton>>,
+    touches: Res<Touches>,
+    mut idle_seconds: Local<HashMap<Entity, f32>>,
+    controllers: Query<(Entity, &OrbitCameraController)>,
+) {
+    // Read all of the events, so none are left over for the next frame.
+    let mouse_moved = mouse_motion_events
+        .iter()
+        .filter(|event| event.delta != Vec2::ZERO)
+        .count()
+        > 0;
+    let mouse_wheel_scrolled = mouse_wheel_reader.iter().count() > 0;
+
+    gamepads.update(&mut gamepad_events);
+
+    let user_input = mouse_moved
+        || mouse_wheel_scrolled
+        || keyboard.get_pressed().next().is_some()
+        || mouse_buttons.get_pressed().next().is_some()
+        || gamepad_buttons.get_pressed().next().is_some()
+        || touches.iter().next().is_some();
+
+    let dt = time.delta_seconds();
+
+    for (camera, controller) in controllers.iter() {
+        if !controller.enabled || controller.auto_rotate_delay.is_none() {
+            idle_seconds.remove(&camera);
+            continue;
+        }
+
+        // Sticks and triggers are analog, so they only count outside of the controller's dead zone.
+        let dead_zone = controller.gamepad_dead_zone;
+        let gamepad_moved = gamepads.left_stick(&gamepad_axes, dead_zone) != Vec2::ZERO
+            || gamepads.right_stick(&gamepad_axes, dead_zone) != Vec2::ZERO
+            || gamepads.trigger(
+                &gamepad_button_axes,
+                GamepadButtonType::LeftTrigger2,
+                dead_zone,
+            ) != 0.0
+            || gamepads.trigger(
+                &gamepad_button_axes,
+                GamepadButtonType::RightTrigger2,
+                dead_zone,
+            ) != 0.0;
+
+        let camera_idle_seconds = idle_seconds.entry(camera).or_insert(0.0);
+        if let Some(delta) = auto_rotate_delta(
+            controller,
+            camera_idle_seconds,
+            user_input || gamepad_moved,
+            dt,
+        ) {
+            events.send(ControlEvent::Orbit(camera, delta));
+        }
     }
+}
 
-    if mouse_buttons.pressed(MouseButton::Right) {
-        events.send(ControlEvent::TranslateTarget(
-            mouse_translate_sensitivity * cursor_delta,
-        ));
+/// Orbits with a one-finger drag, pans with a two-finger drag, and zooms with a pinch.
+pub fn touch_input_map(
+    mut events: EventWriter<ControlEvent>,
+    touches: Res<Touches>,
+    mut last_positions: Local<HashMap<u64, Vec2>>,
+    controllers: Query<(Entity, &OrbitCameraController)>,
+) {
+    // `Touch::previous_position` is only updated when a touch moves, so we remember the positions from the last frame.
+    let mut moves = Vec::new();
+    for touch in touches.iter() {
+        if let Some(&previous) = last_positions.get(&touch.id()) {
+            moves.push((previous, touch.position()));
+        }
     }
+    let num_touches = touches.iter().count();
+    last_positions.clear();
+    last_positions.extend(touches.iter().map(|t| (t.id(), t.position())));
 
-    let mut scalar = 1.0;
-    for event in mouse_wheel_reader.iter() {
-        scalar *= 1.0 + -event.y * mouse_wheel_zoom_sensitivity;
+    // Wait for all fingers to have a previous position, so the gesture doesn't jump when a finger is added.
+    if moves.len() != num_touches {
+        return;
     }
-    events.send(ControlEvent::Zoom(scalar));
+
+    for (camera, controller) in controllers.iter() {
+        let OrbitCameraController {
+            enabled,
+            touch_rotate_sensitivity,
+            ..
+        } = *controller;
+
+        if !enabled {
+            continue;
+        }
+
+        match moves.as_slice() {
+            [(previous, current)] => {
+                events.send(ControlEvent::Orbit(
+                    camera,
+                    touch_rotate_sensitivity * flip_y(*current - *previous),
+                ));
+            }
+            [(previous0, current0), (previous1, current1)] => {
+                let (pan_delta, zoom_scalar) = two_finger_gesture(
+                    (*previous0, *previous1),
+                    (*current0, *current1),
+                    controller,
+                );
+                events.send(ControlEvent::TranslateTarget(camera, pan_delta));
+                if let Some(scalar) = zoom_scalar {
+                    events.send(ControlEvent::Zoom(camera, scalar));
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Maps the `previous` and `current` positions of two fingers to the `TranslateTarget` delta of their center and the `Zoom`
+/// scalar of their pinch. There's no zoom if the fingers are on top of each other.
+fn two_finger_gesture(
+    previous: (Vec2, Vec2),
+    current: (Vec2, Vec2),
+    controller: &OrbitCameraController,
+) -> (Vec2, Option<f32>) {
+    let previous_center = 0.5 * (previous.0 + previous.1);
+    let current_center = 0.5 * (current.0 + current.1);
+    let pan_delta =
+        controller.touch_translate_sensitivity * flip_y(current_center - previous_center);
+
+    let previous_distance = previous.0.distance(previous.1);
+    let current_distance = current.0.distance(current.1);
+    let zoom_scalar = (previous_distance > 0.0 && current_distance > 0.0)
+        .then(|| (previous_distance / current_distance).powf(controller.touch_zoom_sensitivity));
+
+    (pan_delta, zoom_scalar)
 }
 
+/// Touch positions have Y pointing up, but we want deltas to match mouse motion, where Y points down.
+fn flip_y(v: Vec2) -> Vec2 {
+    Vec2::new(v.x, -v.y)
+}
+
+#[allow(clippy::type_complexity)]
 pub fn control_system(
     mut events: EventReader<ControlEvent>,
-    mut cameras: Query<(&OrbitCameraController, &mut LookTransform, &Transform)>,
+    time: Res<Time>,
+    mut cameras: Query<(
+        Entity,
+        &OrbitCameraController,
+        &mut LookTransform,
+        &Transform,
+        Option<&mut OrthographicProjection>,
+        Option<&mut Camera>,
+    )>,
 ) {
-    // Can only control one camera at a time.
-    let (controller, mut transform, scene_transform) =
-        if let Some((controller, transform, scene_transform)) = cameras.iter_mut().next() {
-            (controller, transform, scene_transform)
-        } else {
-            return;
-        };
+    // Collect the events so each camera can pick out the ones addressed to it.
+    let events: Vec<&ControlEvent> = events.iter().collect();
+
+    let dt = time.delta_seconds();
+
+    for (camera, controller, mut transform, scene_transform, orthographic, bevy_camera) in
+        cameras.iter_mut()
+    {
+        if !controller.enabled {
+            continue;
+        }
 
-    if controller.enabled {
-        let mut look_angles = LookAngles::from_vector(-transform.look_direction());
+        let mut look_angles = look_angles_or_last_valid(
+            -transform.look_direction(),
+            transform.up,
+            scene_transform,
+            Vec3::Z,
+        )
+        .with_limits(controller.angle_limits);
         let mut radius_scalar = 1.0;
+        let mut zoom_point = None;
 
-        for event in events.iter() {
+        for event in events.iter().filter(|e| e.camera() == camera) {
             match event {
-                ControlEvent::Orbit(delta) => {
+                ControlEvent::Orbit(_, delta) => {
+                    look_angles.add_yaw(-delta.x);
+                    look_angles.add_pitch(delta.y);
+                }
+                ControlEvent::OrbitAround(_, delta, pivot) => {
+                    let old_direction = look_angles.unit_vector();
                     look_angles.add_yaw(-delta.x);
                     look_angles.add_pitch(delta.y);
+                    transform.target = rotate_about_pivot(
+                        transform.target,
+                        *pivot,
+                        old_direction,
+                        look_angles.unit_vector(),
+                        look_angles.up(),
+                    );
                 }
-                ControlEvent::TranslateTarget(delta) => {
+                ControlEvent::TranslateTarget(_, delta) => {
                     let right_dir = scene_transform.rotation * -Vec3::X;
                     let up_dir = scene_transform.rotation * Vec3::Y;
                     transform.target += delta.x * right_dir + delta.y * up_dir;
                 }
-                ControlEvent::Zoom(scalar) => {
+                ControlEvent::Zoom(_, scalar) => {
+                    radius_scalar *= scalar;
+                }
+                ControlEvent::ZoomToPoint(_, scalar, point) => {
                     radius_scalar *= scalar;
+                    zoom_point = Some(*point);
                 }
             }
         }
 
-        look_angles.assert_not_looking_up();
+        // A zero radius can't be scaled back up, so start over with a unit radius.
+        let old_radius = nonzero_radius(transform.radius());
+        let (radius, applied_zoom) = if let Some(mut orthographic) = orthographic {
+            // Moving the eye doesn't change the size of an orthographic view, so zoom by scaling the projection instead.
+            let old_scale = orthographic.scale;
+            let scale = zoom_orthographic_scale(old_scale, radius_scalar, dt, controller);
+            if scale != old_scale {
+                orthographic.scale = scale;
+
+                // Bevy only recomputes the projection matrix when the window is resized.
+                if let Some(mut bevy_camera) = bevy_camera {
+                    bevy_camera.projection_matrix = orthographic.get_projection_matrix();
+                }
+            }
+            (old_radius, scale / old_scale.max(ABSOLUTE_MIN_ZOOM))
+        } else {
+            let radius = zoom_radius(old_radius, radius_scalar, dt, controller);
+            (radius, radius / old_radius)
+        };
+        if let Some(point) = zoom_point {
+            // Move the target by the zoom that was actually applied, after the zoom limits.
+            transform.target = point + applied_zoom * (transform.target - point);
+        }
+        transform.eye = transform.target + radius * look_angles.unit_vector();
+        transform.up = look_angles.up();
+    }
+}
+
+/// Advances `idle_seconds` by `dt`, or resets it on `user_input`, and returns the `ControlEvent::Orbit` delta that turns the
+/// camera like a turntable once it has been idle for long enough.
+fn auto_rotate_delta(
+    controller: &OrbitCameraController,
+    idle_seconds: &mut f32,
+    user_input: bool,
+    dt: f32,
+) -> Option<Vec2> {
+    if user_input {
+        *idle_seconds = 0.0;
+        return None;
+    }
+
+    *idle_seconds += dt;
+    let delay = controller.auto_rotate_delay?;
+    if *idle_seconds < delay {
+        return None;
+    }
+
+    // Orbiting moves the eye, so the scene appears to turn the opposite way.
+    let sign = match controller.auto_rotate_direction {
+        RotationDirection::Clockwise => -1.0,
+        RotationDirection::Counterclockwise => 1.0,
+    };
+
+    Some(Vec2::new(sign * controller.auto_rotate_speed * dt, 0.0))
+}
+
+/// The ray through the cursor for a camera with either projection.
+fn cursor_ray(
+    windows: &Windows,
+    perspective: Option<&PerspectiveProjection>,
+    orthographic: Option<&OrthographicProjection>,
+    scene_transform: &Transform,
+) -> Option<Ray3d> {
+    match (perspective, orthographic) {
+        (Some(perspective), _) => Ray3d::from_cursor(windows, perspective, scene_transform),
+        (None, Some(orthographic)) => Ray3d::from_cursor(windows, orthographic, scene_transform),
+        (None, None) => None,
+    }
+}
+
+/// The point where the `ray` hits the plane through the target that faces the camera.
+fn point_on_target_plane(ray: &Ray3d, transform: &LookTransform) -> Option<Vec3> {
+    let distance = ray.intersect_plane(transform.target, transform.look_direction())?;
+
+    Some(ray.at(distance))
+}
+
+/// Rotates `point` around `pivot` like the camera turns when the direction from the target to the eye changes from
+/// `old_direction` to `new_direction`.
+fn rotate_about_pivot(
+    point: Vec3,
+    pivot: Vec3,
+    old_direction: Vec3,
+    new_direction: Vec3,
+    up: Vec3,
+) -> Vec3 {
+    let old_rotation = Transform::default().looking_at(old_direction, up).rotation;
+    let new_rotation = Transform::default().looking_at(new_direction, up).rotation;
+
+    pivot + (new_rotation * old_rotation.inverse()) * (point - pivot)
+}
+
+/// Scales the `radius` by the zoom `scalar` and applies the radius limits of the `controller`, where `dt` is the number of
+/// seconds since the last update.
+fn zoom_radius(radius: f32, scalar: f32, dt: f32, controller: &OrbitCameraController) -> f32 {
+    zoom_with_limits(
+        radius,
+        scalar,
+        dt,
+        controller.min_radius,
+        controller.max_radius,
+        controller.elastic_radius_half_life,
+    )
+}
+
+/// Like `zoom_radius`, but for the orthographic scale.
+fn zoom_orthographic_scale(
+    scale: f32,
+    scalar: f32,
+    dt: f32,
+    controller: &OrbitCameraController,
+) -> f32 {
+    zoom_with_limits(
+        scale,
+        scalar,
+        dt,
+        controller.min_orthographic_scale,
+        controller.max_orthographic_scale,
+        controller.elastic_radius_half_life,
+    )
+}
+
+// ████████╗███████╗███████╗████████╗
+// ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝
+//    ██║   █████╗  ███████╗   ██║
+//    ██║   ██╔══╝  ╚════██║   ██║
+//    ██║   ███████╗███████║   ██║
+//    ╚═╝   ╚══════╝╚══════╝   ╚═╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LookAngles;
+
+    use approx::assert_relative_eq;
+    use bevy::{app::Events, input::gamepad::Gamepad, math::const_vec3};
+
+    const DT: f32 = 1.0 / 60.0;
+    const AUTO_ROTATE_EYE: Vec3 = const_vec3!([0.0, 2.0, 5.0]);
+
+    /// A camera looking at the origin with no user input. The `Time` is left at its default, so frames take no time and only
+    /// the events sent to the camera move it.
+    fn auto_rotate_world(controller: OrbitCameraController) -> (World, Entity) {
+        let mut world = World::default();
+        world.insert_resource(Time::default());
+        world.insert_resource(Input::<KeyCode>::default());
+        world.insert_resource(Input::<MouseButton>::default());
+        world.insert_resource(Input::<GamepadButton>::default());
+        world.insert_resource(Axis::<GamepadAxis>::default());
+        world.insert_resource(Axis::<GamepadButton>::default());
+        world.insert_resource(Touches::default());
+        world.insert_resource(Events::<MouseMotion>::default());
+        world.insert_resource(Events::<MouseWheel>::default());
+        world.insert_resource(Events::<GamepadEvent>::default());
+        world.insert_resource(Events::<ControlEvent>::default());
+
+        let look_transform = LookTransform::new(AUTO_ROTATE_EYE, Vec3::ZERO, Vec3::Y);
+        let camera = world
+            .spawn()
+            .insert(controller)
+            .insert(look_transform)
+            .insert(Transform::from(look_transform))
+            .id();
+
+        (world, camera)
+    }
+
+    /// The number of `ControlEvent::Orbit` events the `auto_rotate_system` sends to `camera` in one frame.
+    fn auto_rotate_orbit_events(world: &mut World, camera: Entity) -> usize {
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(auto_rotate_system.system());
+        stage.run(world);
+
+        let events = world.get_resource::<Events<ControlEvent>>().unwrap();
+        events
+            .get_reader()
+            .iter(events)
+            .filter(|event| matches!(event, ControlEvent::Orbit(entity, _) if *entity == camera))
+            .count()
+    }
+
+    fn zoom_repeatedly(controller: &OrbitCameraController, scalar: f32) -> LookTransform {
+        let mut transform = LookTransform::new(Vec3::new(0.0, 2.0, 5.0), Vec3::ZERO, Vec3::Y);
+        for _ in 0..10_000 {
+            let radius = zoom_radius(transform.radius(), scalar, DT, controller);
+            transform.eye = transform.target + radius * -transform.look_direction();
+            assert!(transform.has_orientation());
+        }
+
+        transform
+    }
+
+    #[test]
+    fn test_orbit_around_pivot_keeps_pivot_in_view() {
+        let pivot = Vec3::new(1.0, 0.0, -1.0);
+        let transform = LookTransform::new(Vec3::new(0.0, 2.0, 5.0), Vec3::ZERO, Vec3::Y);
+        let view_space = |t: &LookTransform| {
+            Transform::from(*t)
+                .compute_matrix()
+                .inverse()
+                .transform_point3(pivot)
+        };
+
+        let mut look_angles =
+            LookAngles::from_vector_with_up(-transform.look_direction(), transform.up).unwrap();
+        let old_direction = look_angles.unit_vector();
+        look_angles.add_yaw(0.3);
+        look_angles.add_pitch(-0.2);
+
+        let target = rotate_about_pivot(
+            transform.target,
+            pivot,
+            old_direction,
+            look_angles.unit_vector(),
+            look_angles.up(),
+        );
+        let rotated = LookTransform::new(
+            target + transform.radius() * look_angles.unit_vector(),
+            target,
+            Vec3::Y,
+        );
+
+        let before = view_space(&transform);
+        let after = view_space(&rotated);
+        assert_relative_eq!(before.x, after.x, epsilon = 1e-4);
+        assert_relative_eq!(before.y, after.y, epsilon = 1e-4);
+        assert_relative_eq!(before.z, after.z, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_auto_rotate_turns_like_a_turntable() {
+        for &direction in [
+            RotationDirection::Clockwise,
+            RotationDirection::Counterclockwise,
+        ]
+        .iter()
+        {
+            let controller = OrbitCameraController {
+                auto_rotate_delay: Some(0.0),
+                auto_rotate_direction: direction,
+                ..Default::default()
+            };
+            let delta = auto_rotate_delta(&controller, &mut 0.0, false, DT).unwrap();
+
+            let (mut world, camera) = auto_rotate_world(controller);
+            world
+                .get_resource_mut::<Events<ControlEvent>>()
+                .unwrap()
+                .send(ControlEvent::Orbit(camera, delta));
+            let mut stage = SystemStage::single_threaded();
+            stage.add_system(control_system.system());
+            stage.run(&mut world);
+
+            let eye = world.get::<LookTransform>(camera).unwrap().eye;
+            assert_relative_eq!(eye.y, AUTO_ROTATE_EYE.y, epsilon = 1e-5);
+            assert_relative_eq!(eye.length(), AUTO_ROTATE_EYE.length(), epsilon = 1e-4);
+
+            // The scene turns one way as seen from above, so the eye moves the other way around the up axis.
+            let turn = AUTO_ROTATE_EYE.cross(eye).y;
+            match direction {
+                RotationDirection::Clockwise => assert!(turn > 0.0),
+                RotationDirection::Counterclockwise => assert!(turn < 0.0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_auto_rotate_waits_for_idle_input() {
+        let controller = OrbitCameraController {
+            auto_rotate_delay: Some(1.0),
+            auto_rotate_speed: 0.5,
+            ..Default::default()
+        };
+
+        // Not idle for long enough until a second has passed.
+        let mut idle_seconds = 0.0;
+        for _ in 0..59 {
+            assert_eq!(
+                auto_rotate_delta(&controller, &mut idle_seconds, false, DT),
+                None
+            );
+        }
+        let delta = auto_rotate_delta(&controller, &mut idle_seconds, false, 2.0 * DT).unwrap();
+        assert_relative_eq!(delta.x, 0.5 * 2.0 * DT);
+        assert_eq!(delta.y, 0.0);
+
+        // Input restarts the wait.
+        assert_eq!(
+            auto_rotate_delta(&controller, &mut idle_seconds, true, DT),
+            None
+        );
+        assert_eq!(idle_seconds, 0.0);
+        assert_eq!(
+            auto_rotate_delta(&controller, &mut idle_seconds, false, DT),
+            None
+        );
+
+        // Disabled by default.
+        let mut idle_seconds = 0.0;
+        assert_eq!(
+            auto_rotate_delta(
+                &OrbitCameraController::default(),
+                &mut idle_seconds,
+                false,
+                1000.0
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_auto_rotate_system_respects_gamepad_input() {
+        let controller = OrbitCameraController {
+            auto_rotate_delay: Some(0.0),
+            ..Default::default()
+        };
+
+        let (mut world, camera) = auto_rotate_world(controller);
+        assert_eq!(auto_rotate_orbit_events(&mut world, camera), 1);
+
+        // Pressing a gamepad button counts as input.
+        let (mut world, camera) = auto_rotate_world(controller);
+        world
+            .get_resource_mut::<Input<GamepadButton>>()
+            .unwrap()
+            .press(GamepadButton(Gamepad(0), GamepadButtonType::South));
+        assert_eq!(auto_rotate_orbit_events(&mut world, camera), 0);
+    }
+
+    #[test]
+    fn test_pinch_in_zooms_out() {
+        let controller = OrbitCameraController::default();
+        let (pan_delta, zoom_scalar) = two_finger_gesture(
+            (Vec2::new(-100.0, 0.0), Vec2::new(100.0, 0.0)),
+            (Vec2::new(-50.0, 0.0), Vec2::new(50.0, 0.0)),
+            &controller,
+        );
+
+        assert_eq!(pan_delta, Vec2::ZERO);
+        assert_relative_eq!(zoom_scalar.unwrap(), 2.0);
+    }
+
+    #[test]
+    fn test_pinch_out_zooms_in() {
+        let controller = OrbitCameraController {
+            touch_zoom_sensitivity: 2.0,
+            ..Default::default()
+        };
+        let (pan_delta, zoom_scalar) = two_finger_gesture(
+            (Vec2::new(0.0, -50.0), Vec2::new(0.0, 50.0)),
+            (Vec2::new(0.0, -100.0), Vec2::new(0.0, 100.0)),
+            &controller,
+        );
+
+        assert_eq!(pan_delta, Vec2::ZERO);
+        assert_relative_eq!(zoom_scalar.unwrap(), 0.25);
+    }
+
+    #[test]
+    fn test_parallel_two_finger_drag_pans() {
+        let controller = OrbitCameraController {
+            touch_translate_sensitivity: Vec2::splat(0.5),
+            ..Default::default()
+        };
+        let offset = Vec2::new(10.0, 20.0);
+        let (pan_delta, zoom_scalar) = two_finger_gesture(
+            (Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0)),
+            (Vec2::new(0.0, 0.0) + offset, Vec2::new(100.0, 0.0) + offset),
+            &controller,
+        );
+
+        // Touch Y points up, but the delta matches mouse motion, where Y points down.
+        assert_relative_eq!(pan_delta.x, 5.0);
+        assert_relative_eq!(pan_delta.y, -10.0);
+        assert_relative_eq!(zoom_scalar.unwrap(), 1.0);
+
+        let (_, zoom_scalar) =
+            two_finger_gesture((Vec2::ZERO, Vec2::ZERO), (Vec2::ZERO, Vec2::X), &controller);
+        assert!(zoom_scalar.is_none());
+    }
+
+    #[test]
+    fn test_hard_radius_limits() {
+        let controller = OrbitCameraController {
+            min_radius: 1.0,
+            max_radius: 10.0,
+            ..Default::default()
+        };
+
+        let zoomed_in = zoom_repeatedly(&controller, 0.5);
+        assert_relative_eq!(zoomed_in.radius(), 1.0, epsilon = 1e-4);
+
+        let zoomed_out = zoom_repeatedly(&controller, 2.0);
+        assert_relative_eq!(zoomed_out.radius(), 10.0, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_zero_min_radius_is_not_degenerate() {
+        let controller = OrbitCameraController {
+            min_radius: 0.0,
+            ..Default::default()
+        };
+
+        let zoomed_in = zoom_repeatedly(&controller, 0.5);
+        assert!(zoomed_in.radius() > 0.0);
+
+        // Even a nonsensical zoom scalar is safe.
+        let zoomed_in = zoom_repeatedly(&controller, -1.0);
+        assert!(zoomed_in.radius() > 0.0);
+    }
+
+    #[test]
+    fn test_orthographic_scale_limits() {
+        let controller = OrbitCameraController {
+            min_orthographic_scale: 0.01,
+            max_orthographic_scale: 2.0,
+            ..Default::default()
+        };
+
+        let mut scale = 1.0;
+        for _ in 0..1000 {
+            scale = zoom_orthographic_scale(scale, 0.5, DT, &controller);
+        }
+        assert_relative_eq!(scale, 0.01, epsilon = 1e-5);
+
+        for _ in 0..1000 {
+            scale = zoom_orthographic_scale(scale, 2.0, DT, &controller);
+        }
+        assert_relative_eq!(scale, 2.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_elastic_radius_limits() {
+        let controller = OrbitCameraController {
+            min_radius: 1.0,
+            max_radius: 10.0,
+            elastic_radius_half_life: Some(0.1),
+            ..Default::default()
+        };
+
+        // Zooming in the whole time overshoots the limit, but only so far.
+        let zoomed_in = zoom_repeatedly(&controller, 0.9);
+        assert!(zoomed_in.radius() < 1.0);
+        assert!(zoomed_in.radius() >= 0.5);
+
+        // Letting go springs back to the limit.
+        let mut radius = zoomed_in.radius();
+        for _ in 0..120 {
+            radius = zoom_radius(radius, 1.0, DT, &controller);
+        }
+        assert_relative_eq!(radius, 1.0, epsilon = 1e-3);
 
-        transform.eye =
-            transform.target + radius_scalar * transform.radius() * look_angles.unit_vector();
-    } else {
-        events.iter(); // Drop the events.
+        let zoomed_out = zoom_repeatedly(&controller, 1.1);
+        assert!(zoomed_out.radius() > 10.0);
+        assert!(zoomed_out.radius() <= 20.0);
     }
 }
@@ -0,0 +1,601 @@
+use crate::{controllers::InputBinding, LookTransform, Ray3d, SMOOTHER_REFERENCE_FRAME_SECONDS};
+
+use bevy::{
+    app::prelude::*,
+    core::Time,
+    ecs::{bundle::Bundle, prelude::*},
+    input::{
+        mouse::{MouseMotion, MouseWheel},
+        prelude::*,
+    },
+    math::prelude::*,
+    render::{
+        camera::{Camera, CameraProjection, OrthographicProjection},
+        prelude::*,
+    },
+    transform::components::Transform,
+    utils::HashMap,
+    window::Windows,
+};
+use serde::{Deserialize, Serialize};
+
+pub struct Ortho2dCameraPlugin;
+
+impl Plugin for Ortho2dCameraPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Ortho2dCameraBindings>()
+            .add_system(default_input_map.system())
+            .add_system(control_system.system())
+            .add_event::<ControlEvent>();
+    }
+}
+
+#[derive(Bundle)]
+pub struct Ortho2dCameraBundle {
+    controller: Ortho2dCameraController,
+    view: Ortho2dView,
+    // No `Smoother`, since the `control_system` smooths the center and the scale together.
+    look_transform: LookTransform,
+    #[bundle]
+    orthographic: OrthographicCameraBundle,
+}
+
+impl Ortho2dCameraBundle {
+    /// Centers the view on `position` in the XY plane. The camera keeps the depth of the `orthographic` bundle's transform, so
+    /// start with `OrthographicCameraBundle::new_2d()`.
+    pub fn new(
+        controller: Ortho2dCameraController,
+        mut orthographic: OrthographicCameraBundle,
+        position: Vec2,
+    ) -> Self {
+        let eye = position.extend(orthographic.transform.translation.z);
+        let target = position.extend(0.0);
+
+        // Make sure the transform is consistent with the controller to start.
+        orthographic.transform = Transform::from_translation(eye).looking_at(target, Vec3::Y);
+
+        Self {
+            controller,
+            view: Ortho2dView {
+                center: position,
+                scale: orthographic.orthographic_projection.scale,
+            },
+            look_transform: LookTransform::new(eye, target, Vec3::Y),
+            orthographic,
+        }
+    }
+}
+
+/// A camera for 2D games and map views that pans in the XY plane and zooms by changing the orthographic scale.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Ortho2dCameraController {
+    pub enabled: bool,
+    /// Multiples of the view's height per second while a pan key is held.
+    pub key_pan_sensitivity: f32,
+    /// Fraction of the scale per line of mouse wheel scrolling.
+    pub mouse_wheel_zoom_sensitivity: f32,
+    /// If set, the mouse wheel zooms toward the point under the cursor instead of the center of the view.
+    pub zoom_to_cursor: bool,
+    /// The smallest orthographic scale, i.e. the furthest you can zoom in.
+    pub min_scale: f32,
+    /// The largest orthographic scale, i.e. the furthest you can zoom out.
+    pub max_scale: f32,
+    /// The min and max corners of the area that the center of the view is kept in.
+    pub world_bounds: Option<(Vec2, Vec2)>,
+    /// The lag weight of panning and zooming, between `0.0` and `1.0`, where higher is smoother. The eye and target of a 2D
+    /// view move together, so unlike the other controllers there are no separate eye and target weights.
+    pub smoothing_weight: f32,
+}
+
+impl Default for Ortho2dCameraController {
+    fn default() -> Self {
+        Self {
+            key_pan_sensitivity: 1.0,
+            mouse_wheel_zoom_sensitivity: 0.15,
+            zoom_to_cursor: true,
+            min_scale: 0.05,
+            max_scale: 20.0,
+            world_bounds: None,
+            smoothing_weight: 0.8,
+            enabled: true,
+        }
+    }
+}
+
+/// The view that an `Ortho2dCameraController` is moving to. The `LookTransform` and the projection's scale are smoothed
+/// towards it together, so a point that was zoomed to stays under the cursor the whole way. Changing the `LookTransform` or
+/// the projection's scale from elsewhere, e.g. with a `FrameEvent`, sets a new view here, which is smoothed towards the same
+/// way.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ortho2dView {
+    pub center: Vec2,
+    /// The orthographic scale.
+    pub scale: f32,
+}
+
+/// The inputs read by `ortho_2d::default_input_map`. Insert this resource before adding the `Ortho2dCameraPlugin` to override
+/// the defaults, e.g. after deserializing it from a config file.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Ortho2dCameraBindings {
+    /// Held while dragging the mouse to pan.
+    pub pan_drag: InputBinding,
+    pub up: InputBinding,
+    pub left: InputBinding,
+    pub down: InputBinding,
+    pub right: InputBinding,
+}
+
+impl Default for Ortho2dCameraBindings {
+    fn default() -> Self {
+        Self {
+            pan_drag: InputBinding::Mouse(MouseButton::Right),
+            up: InputBinding::Key(KeyCode::W),
+            left: InputBinding::Key(KeyCode::A),
+            down: InputBinding::Key(KeyCode::S),
+            right: InputBinding::Key(KeyCode::D),
+        }
+    }
+}
+
+/// An input for the camera entity it is addressed to.
+pub enum ControlEvent {
+    /// Moves the view, in world units.
+    Pan(Entity, Vec2),
+    /// Multiplies the scale, keeping the center of the view in place.
+    Zoom(Entity, f32),
+    /// Multiplies the scale, keeping the given world point in place on the screen.
+    ZoomToPoint(Entity, f32, Vec2),
+}
+
+impl ControlEvent {
+    /// The camera entity that should handle this event.
+    pub fn camera(&self) -> Entity {
+        match *self {
+            Self::Pan(camera, _) | Self::Zoom(camera, _) | Self::ZoomToPoint(camera, _, _) => {
+                camera
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn default_input_map(
+    mut events: EventWriter<ControlEvent>,
+    time: Res<Time>,
+    bindings: Res<Ortho2dCameraBindings>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    mut mouse_wheel_reader: EventReader<MouseWheel>,
+    windows: Res<Windows>,
+    controllers: Query<(
+        Entity,
+        &Ortho2dCameraController,
+        &OrthographicProjection,
+        &Transform,
+    )>,
+) {
+    let mut cursor_delta = Vec2::ZERO;
+    for event in mouse_motion_events.iter() {
+        cursor_delta += event.delta;
+    }
+
+    let wheel_deltas: Vec<f32> = mouse_wheel_reader.iter().map(|event| event.y).collect();
+
+    let mut key_pan = Vec2::ZERO;
+    for (binding, dir) in [
+        (bindings.up, Vec2::Y),
+        (bindings.left, -Vec2::X),
+        (bindings.down, -Vec2::Y),
+        (bindings.right, Vec2::X),
+    ]
+    .iter()
+    .cloned()
+    {
+        if binding.pressed(&keyboard, &mouse_buttons) {
+            key_pan += dir;
+        }
+    }
+
+    let window_size = windows
+        .get_primary()
+        .map(|window| Vec2::new(window.width(), window.height()));
+
+    let dt = time.delta_seconds();
+
+    for (camera, controller, projection, scene_transform) in controllers.iter() {
+        let Ortho2dCameraController {
+            enabled,
+            key_pan_sensitivity,
+            mouse_wheel_zoom_sensitivity,
+            zoom_to_cursor,
+            ..
+        } = *controller;
+
+        if !enabled {
+            continue;
+        }
+
+        let view_size = projection.scale
+            * Vec2::new(
+                projection.right - projection.left,
+                projection.top - projection.bottom,
+            );
+
+        // Drag the world along with the cursor. Mouse motion has Y pointing down.
+        if let Some(window_size) = window_size {
+            if bindings.pan_drag.pressed(&keyboard, &mouse_buttons) && window_size.x > 0.0 {
+                let units_per_pixel = view_size.x / window_size.x;
+                events.send(ControlEvent::Pan(
+                    camera,
+                    units_per_pixel * Vec2::new(-cursor_delta.x, cursor_delta.y),
+                ));
+            }
+        }
+
+        if key_pan != Vec2::ZERO {
+            events.send(ControlEvent::Pan(
+                camera,
+                key_pan_sensitivity * view_size.y * dt * key_pan.normalize(),
+            ));
+        }
+
+        if wheel_deltas.is_empty() {
+            continue;
+        }
+        let mut scalar = 1.0;
+        for delta in wheel_deltas.iter() {
+            scalar *= 1.0 + -delta * mouse_wheel_zoom_sensitivity;
+        }
+        let cursor_point = if zoom_to_cursor {
+            Ray3d::from_cursor(&windows, projection, scene_transform)
+                .map(|ray| ray.origin.truncate())
+        } else {
+            None
+        };
+        if let Some(point) = cursor_point {
+            events.send(ControlEvent::ZoomToPoint(camera, scalar, point));
+        } else {
+            events.send(ControlEvent::Zoom(camera, scalar));
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn control_system(
+    mut events: EventReader<ControlEvent>,
+    time: Res<Time>,
+    mut cameras: Query<(
+        Entity,
+        &Ortho2dCameraController,
+        &mut Ortho2dView,
+        &mut LookTransform,
+        &mut OrthographicProjection,
+        Option<&mut Camera>,
+    )>,
+    mut smoothed_views: Local<HashMap<Entity, Ortho2dView>>,
+) {
+    // Collect the events so each camera can pick out the ones addressed to it.
+    let events: Vec<&ControlEvent> = events.iter().collect();
+
+    for (camera, controller, mut view, mut transform, mut projection, bevy_camera) in
+        cameras.iter_mut()
+    {
+        if !controller.enabled {
+            smoothed_views.remove(&camera);
+            continue;
+        }
+
+        let mut current = Ortho2dView {
+            center: transform.target.truncate(),
+            scale: projection.scale,
+        };
+
+        // Something else moved the camera since the last frame, so make that the new view and smooth towards it from where
+        // the camera was.
+        if let Some(&smoothed) = smoothed_views.get(&camera) {
+            if current != smoothed {
+                *view = current;
+                current = smoothed;
+            }
+        }
+        for event in events.iter().filter(|e| e.camera() == camera) {
+            match event {
+                ControlEvent::Pan(_, delta) => {
+                    view.center += *delta;
+                }
+                ControlEvent::Zoom(_, scalar) => {
+                    view.scale = clamp_scale(view.scale * scalar, controller);
+                }
+                ControlEvent::ZoomToPoint(_, scalar, point) => {
+                    let new_scale = clamp_scale(view.scale * scalar, controller);
+                    // The point is under the cursor in the current view, which may still be on its way to the last one.
+                    view.center = zoom_about_point(
+                        current.center,
+                        *point,
+                        new_scale / current.scale.max(f32::EPSILON),
+                    );
+                    view.scale = new_scale;
+                }
+            }
+        }
+
+        if let Some((min, max)) = controller.world_bounds {
+            view.center = view.center.max(min).min(max);
+        }
+
+        let smoothed = smooth_view(
+            current,
+            *view,
+            controller.smoothing_weight,
+            time.delta_seconds(),
+        );
+
+        let offset = smoothed.center - transform.target.truncate();
+        transform.target += offset.extend(0.0);
+        transform.eye += offset.extend(0.0);
+
+        if projection.scale != smoothed.scale {
+            projection.scale = smoothed.scale;
+
+            // Bevy only recomputes the projection matrix when the window is resized.
+            if let Some(mut bevy_camera) = bevy_camera {
+                bevy_camera.projection_matrix = projection.get_projection_matrix();
+            }
+        }
+
+        smoothed_views.insert(
+            camera,
+            Ortho2dView {
+                center: transform.target.truncate(),
+                scale: projection.scale,
+            },
+        );
+    }
+}
+
+fn clamp_scale(scale: f32, controller: &Ortho2dCameraController) -> f32 {
+    let min = controller.min_scale.max(f32::EPSILON);
+
+    scale.max(min).min(controller.max_scale.max(min))
+}
+
+/// The new center of the view after scaling it by `ratio`, such that `point` stays in the same place on the screen.
+fn zoom_about_point(center: Vec2, point: Vec2, ratio: f32) -> Vec2 {
+    point + ratio * (center - point)
+}
+
+/// Exponentially smooths the `current` view towards the `desired` one. The scale is smoothed in log space, so zooming in
+/// looks as fast as zooming out, and the center follows from the scale such that any point that is in the same place on the
+/// screen in both views stays there in between.
+fn smooth_view(
+    current: Ortho2dView,
+    desired: Ortho2dView,
+    lag_weight: f32,
+    dt: f32,
+) -> Ortho2dView {
+    if current.scale <= 0.0 || desired.scale <= 0.0 {
+        return desired;
+    }
+
+    let lag = lag_weight
+        .clamp(0.0, 1.0)
+        .powf(dt / SMOOTHER_REFERENCE_FRAME_SECONDS);
+    let scale = (desired.scale.ln() + lag * (current.scale.ln() - desired.scale.ln())).exp();
+
+    // Scaling about the fixed point `p` gives `center = p + (scale / current.scale) * (current.center - p)`, rearranged so
+    // that it doesn't divide by zero when only panning. `progress` goes from `0.0` at the current scale to `1.0` at the
+    // desired one.
+    let progress = if (desired.scale / current.scale - 1.0).abs() < 1e-4 {
+        1.0 - lag
+    } else {
+        (current.scale - scale) / (current.scale - desired.scale)
+    };
+    let center = (scale / current.scale) * current.center
+        + progress * (desired.center - (desired.scale / current.scale) * current.center);
+
+    // Stop once the change is invisible, so the projection isn't updated forever.
+    if (scale / desired.scale - 1.0).abs() < 1e-4
+        && center.distance(desired.center) < 1e-3 * desired.scale
+    {
+        desired
+    } else {
+        Ortho2dView { center, scale }
+    }
+}
+
+// ████████╗███████╗███████╗████████╗
+// ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝
+//    ██║   █████╗  ███████╗   ██║
+//    ██║   ██╔══╝  ╚════██║   ██║
+//    ██║   ███████╗███████║   ██║
+//    ╚═╝   ╚══════╝╚══════╝   ╚═╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{frame_orthographic, frame_system, Bounds, FrameEvent};
+
+    use approx::assert_relative_eq;
+    use bevy::app::Events;
+
+    /// Updates the `Time` for the next frame and returns its delta, which is however long has passed since the last update.
+    fn next_frame_dt(world: &mut World) -> f32 {
+        let mut time = world.get_resource_mut::<Time>().unwrap();
+        time.update();
+        let dt = time.delta_seconds();
+        assert!(dt > 0.0);
+
+        dt
+    }
+
+    #[test]
+    fn test_zoom_about_point_keeps_point_on_screen() {
+        let center = Vec2::new(10.0, 0.0);
+        let point = Vec2::new(14.0, 3.0);
+        let (old_scale, new_scale) = (2.0, 0.5);
+
+        let new_center = zoom_about_point(center, point, new_scale / old_scale);
+
+        // The point's offset from the center, in pixels, doesn't change.
+        let old_pixels = (point - center) / old_scale;
+        let new_pixels = (point - new_center) / new_scale;
+        assert_relative_eq!(old_pixels.x, new_pixels.x, epsilon = 1e-5);
+        assert_relative_eq!(old_pixels.y, new_pixels.y, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_scale_limits() {
+        let controller = Ortho2dCameraController {
+            min_scale: 0.5,
+            max_scale: 4.0,
+            ..Default::default()
+        };
+
+        assert_relative_eq!(clamp_scale(0.1, &controller), 0.5);
+        assert_relative_eq!(clamp_scale(1.0, &controller), 1.0);
+        assert_relative_eq!(clamp_scale(100.0, &controller), 4.0);
+    }
+
+    #[test]
+    fn test_smooth_view_converges() {
+        let dt = 1.0 / 60.0;
+        let desired = Ortho2dView {
+            center: Vec2::new(5.0, -2.0),
+            scale: 4.0,
+        };
+
+        let mut view = Ortho2dView {
+            center: Vec2::ZERO,
+            scale: 1.0,
+        };
+        view = smooth_view(view, desired, 0.8, dt);
+        assert!(view.scale > 1.0 && view.scale < 4.0);
+
+        for _ in 0..600 {
+            view = smooth_view(view, desired, 0.8, dt);
+        }
+        assert_eq!(view, desired);
+
+        // Panning alone moves the center part of the way.
+        let panned = Ortho2dView {
+            center: Vec2::new(10.0, 0.0),
+            scale: 4.0,
+        };
+        let view = smooth_view(desired, panned, 0.8, dt);
+        assert_eq!(view.scale, 4.0);
+        assert!(view.center.x > 5.0 && view.center.x < 10.0);
+
+        // No smoothing jumps straight to the desired view.
+        assert_eq!(smooth_view(view, desired, 0.0, dt), desired);
+    }
+
+    #[test]
+    fn test_smoothed_zoom_keeps_point_on_screen() {
+        let dt = 1.0 / 60.0;
+        let point = Vec2::new(14.0, 3.0);
+        let start = Ortho2dView {
+            center: Vec2::new(10.0, 0.0),
+            scale: 2.0,
+        };
+        let desired = Ortho2dView {
+            center: zoom_about_point(start.center, point, 0.5 / start.scale),
+            scale: 0.5,
+        };
+
+        let start_pixels = (point - start.center) / start.scale;
+        let mut view = start;
+        for _ in 0..30 {
+            view = smooth_view(view, desired, 0.8, dt);
+
+            // Every frame in between, the point is where it was on the screen.
+            let pixels = (point - view.center) / view.scale;
+            assert_relative_eq!(pixels.x, start_pixels.x, epsilon = 1e-3);
+            assert_relative_eq!(pixels.y, start_pixels.y, epsilon = 1e-3);
+        }
+        assert!(view.scale > 0.5 && view.scale < 2.0);
+    }
+
+    #[test]
+    fn test_framing_settles_on_bounds() {
+        let mut world = World::default();
+        world.insert_resource(Time::default());
+        world.insert_resource(Events::<ControlEvent>::default());
+        world.insert_resource(Events::<FrameEvent>::default());
+
+        let mut orthographic = OrthographicCameraBundle::new_2d();
+        orthographic.orthographic_projection.update(800.0, 600.0);
+        let bundle =
+            Ortho2dCameraBundle::new(Ortho2dCameraController::default(), orthographic, Vec2::ZERO);
+        let start = bundle.view;
+        let camera = world.spawn().insert_bundle(bundle).id();
+
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(frame_system.system().label("frame"));
+        stage.add_system(control_system.system().after("frame"));
+        stage.run(&mut world);
+
+        let bounds = Bounds::Sphere {
+            center: Vec3::new(50.0, 30.0, 0.0),
+            radius: 30.0,
+        };
+        let (framed, framed_scale) = frame_orthographic(
+            world.get::<LookTransform>(camera).unwrap(),
+            &bounds,
+            0.0,
+            world.get::<OrthographicProjection>(camera).unwrap(),
+        );
+        world
+            .get_resource_mut::<Events<FrameEvent>>()
+            .unwrap()
+            .send(FrameEvent {
+                camera,
+                bounds,
+                padding: 0.0,
+            });
+        stage.run(&mut world);
+
+        // The framed view is the new goal, and the camera is smoothed towards it rather than jumping.
+        let goal = Ortho2dView {
+            center: framed.target.truncate(),
+            scale: framed_scale,
+        };
+        assert_eq!(*world.get::<Ortho2dView>(camera).unwrap(), goal);
+        assert_eq!(
+            world
+                .get::<LookTransform>(camera)
+                .unwrap()
+                .target
+                .truncate(),
+            start.center
+        );
+        assert_eq!(
+            world.get::<OrthographicProjection>(camera).unwrap().scale,
+            start.scale
+        );
+
+        // Start the clock, so the following frames have a delta.
+        world.get_resource_mut::<Time>().unwrap().update();
+        for _ in 0..100 {
+            // Real time passes between frames, so pick the lag weight that closes half of the remaining distance in each
+            // frame, rather than letting the smoothing depend on how fast the test runs.
+            let dt = next_frame_dt(&mut world);
+            world
+                .get_mut::<Ortho2dCameraController>(camera)
+                .unwrap()
+                .smoothing_weight = 0.5f32.powf(SMOOTHER_REFERENCE_FRAME_SECONDS / dt);
+            stage.run(&mut world);
+        }
+
+        // The camera settled on the framed bounds, without being pulled back to where it was.
+        assert_eq!(*world.get::<Ortho2dView>(camera).unwrap(), goal);
+        let target = world.get::<LookTransform>(camera).unwrap().target;
+        assert_relative_eq!(target.x, bounds.center().x, epsilon = 1e-3);
+        assert_relative_eq!(target.y, bounds.center().y, epsilon = 1e-3);
+        assert_eq!(
+            world.get::<OrthographicProjection>(camera).unwrap().scale,
+            framed_scale
+        );
+    }
+}
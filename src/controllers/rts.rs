@@ -0,0 +1,544 @@
+use crate::{
+    controllers::{look_angles_or_last_valid, InputBinding},
+    LookAngles, LookTransform, LookTransformBundle, Smoother,
+};
+
+use bevy::{
+    app::prelude::*,
+    core::Time,
+    ecs::{bundle::Bundle, prelude::*},
+    input::{
+        mouse::{MouseMotion, MouseWheel},
+        prelude::*,
+    },
+    math::prelude::*,
+    render::prelude::*,
+    transform::components::Transform,
+    window::Windows,
+};
+use serde::{Deserialize, Serialize};
+
+pub struct RtsCameraPlugin;
+
+impl Plugin for RtsCameraPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<RtsCameraBindings>()
+            .add_system(default_input_map.system())
+            .add_system(control_system.system())
+            .add_event::<ControlEvent>();
+    }
+}
+
+#[derive(Bundle)]
+pub struct RtsCameraBundle {
+    controller: RtsCameraController,
+    #[bundle]
+    look_transform: LookTransformBundle,
+    #[bundle]
+    perspective: PerspectiveCameraBundle,
+}
+
+impl RtsCameraBundle {
+    pub fn new(
+        controller: RtsCameraController,
+        mut perspective: PerspectiveCameraBundle,
+        eye: Vec3,
+        target: Vec3,
+        up: Vec3,
+    ) -> Self {
+        // Make sure the transform is consistent with the controller to start.
+        perspective.transform = Transform::from_translation(eye).looking_at(target, up);
+
+        Self {
+            controller,
+            look_transform: LookTransformBundle {
+                transform: LookTransform::new(eye, target, up),
+                smoother: Smoother::new_separate(
+                    controller.smoothing_weight,
+                    controller.eye_smoothing_weight,
+                    controller.target_smoothing_weight,
+                ),
+            },
+            perspective,
+        }
+    }
+}
+
+/// A top-down strategy game camera. The target is the center of the view on the ground, and the eye's height and pitch
+/// above it both follow the zoom level.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct RtsCameraController {
+    pub enabled: bool,
+    /// Multiples of the eye's height per second while panning with keys or edge scrolling, so panning looks equally fast
+    /// at any zoom level.
+    pub pan_sensitivity: f32,
+    /// Pixels from the edge of the window where the cursor pans the camera. `0.0` disables edge scrolling.
+    pub edge_scroll_margin: f32,
+    /// Radians per pixel of mouse motion.
+    pub mouse_rotate_sensitivity: f32,
+    /// Radians per second while a rotate key is held.
+    pub key_rotate_sensitivity: f32,
+    /// Zoom levels per line of mouse wheel scrolling, where zoom levels go from `0.0` (closest) to `1.0` (furthest).
+    pub mouse_wheel_zoom_sensitivity: f32,
+    /// The eye's height above the target when zoomed in all the way.
+    pub min_height: f32,
+    /// The eye's height above the target when zoomed out all the way.
+    pub max_height: f32,
+    /// Radians that the eye looks down from the horizon when zoomed in all the way.
+    pub min_pitch: f32,
+    /// Radians that the eye looks down from the horizon when zoomed out all the way.
+    pub max_pitch: f32,
+    /// The min and max corners of the box that the target is kept in, e.g. the playable area of the map.
+    pub map_bounds: Option<(Vec3, Vec3)>,
+    /// The `Smoother` lag weight of the eye and target, between `0.0` and `1.0`, where higher is smoother.
+    pub smoothing_weight: f32,
+    /// Overrides the `smoothing_weight` of the eye.
+    pub eye_smoothing_weight: Option<f32>,
+    /// Overrides the `smoothing_weight` of the target.
+    pub target_smoothing_weight: Option<f32>,
+}
+
+impl Default for RtsCameraController {
+    fn default() -> Self {
+        Self {
+            pan_sensitivity: 1.0,
+            edge_scroll_margin: 10.0,
+            mouse_rotate_sensitivity: 0.006,
+            key_rotate_sensitivity: 1.5,
+            mouse_wheel_zoom_sensitivity: 0.1,
+            min_height: 5.0,
+            max_height: 100.0,
+            min_pitch: 0.5,
+            max_pitch: 1.3,
+            map_bounds: None,
+            smoothing_weight: 0.8,
+            eye_smoothing_weight: None,
+            target_smoothing_weight: None,
+            enabled: true,
+        }
+    }
+}
+
+/// The inputs read by `rts::default_input_map`. Insert this resource before adding the `RtsCameraPlugin` to override the
+/// defaults, e.g. after deserializing it from a config file. Each action is triggered by any of its bindings.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RtsCameraBindings {
+    pub forward: Vec<InputBinding>,
+    pub left: Vec<InputBinding>,
+    pub backward: Vec<InputBinding>,
+    pub right: Vec<InputBinding>,
+    pub rotate_left: Vec<InputBinding>,
+    pub rotate_right: Vec<InputBinding>,
+    /// Held while dragging the mouse to rotate.
+    pub rotate_drag: Vec<InputBinding>,
+}
+
+impl Default for RtsCameraBindings {
+    fn default() -> Self {
+        Self {
+            forward: vec![
+                InputBinding::Key(KeyCode::W),
+                InputBinding::Key(KeyCode::Up),
+            ],
+            left: vec![
+                InputBinding::Key(KeyCode::A),
+                InputBinding::Key(KeyCode::Left),
+            ],
+            backward: vec![
+                InputBinding::Key(KeyCode::S),
+                InputBinding::Key(KeyCode::Down),
+            ],
+            right: vec![
+                InputBinding::Key(KeyCode::D),
+                InputBinding::Key(KeyCode::Right),
+            ],
+            rotate_left: vec![InputBinding::Key(KeyCode::Q)],
+            rotate_right: vec![InputBinding::Key(KeyCode::E)],
+            rotate_drag: vec![InputBinding::Mouse(MouseButton::Middle)],
+        }
+    }
+}
+
+/// An input for the camera entity it is addressed to.
+pub enum ControlEvent {
+    /// Moves the target on the ground, where X is to the right and Y is forward, in multiples of the eye's height.
+    Pan(Entity, Vec2),
+    /// Rotates around the target, in radians.
+    Rotate(Entity, f32),
+    /// Changes the zoom level, where zoom levels go from `0.0` (closest) to `1.0` (furthest).
+    Zoom(Entity, f32),
+}
+
+impl ControlEvent {
+    /// The camera entity that should handle this event.
+    pub fn camera(&self) -> Entity {
+        match *self {
+            Self::Pan(camera, _) | Self::Rotate(camera, _) | Self::Zoom(camera, _) => camera,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn default_input_map(
+    mut events: EventWriter<ControlEvent>,
+    time: Res<Time>,
+    bindings: Res<RtsCameraBindings>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    mut mouse_wheel_reader: EventReader<MouseWheel>,
+    windows: Res<Windows>,
+    controllers: Query<(Entity, &RtsCameraController)>,
+) {
+    let mut cursor_delta = Vec2::ZERO;
+    for event in mouse_motion_events.iter() {
+        cursor_delta += event.delta;
+    }
+
+    let mut wheel_delta = 0.0;
+    for event in mouse_wheel_reader.iter() {
+        wheel_delta += event.y;
+    }
+
+    let any_pressed = |actions: &[InputBinding]| {
+        actions
+            .iter()
+            .any(|binding| binding.pressed(&keyboard, &mouse_buttons))
+    };
+
+    let mut key_pan = Vec2::ZERO;
+    for (actions, dir) in [
+        (&bindings.forward, Vec2::Y),
+        (&bindings.left, -Vec2::X),
+        (&bindings.backward, -Vec2::Y),
+        (&bindings.right, Vec2::X),
+    ]
+    .iter()
+    {
+        if any_pressed(actions) {
+            key_pan += *dir;
+        }
+    }
+
+    let mut key_rotate = 0.0;
+    if any_pressed(&bindings.rotate_left) {
+        key_rotate -= 1.0;
+    }
+    if any_pressed(&bindings.rotate_right) {
+        key_rotate += 1.0;
+    }
+
+    let cursor = windows.get_primary().and_then(|window| {
+        window
+            .cursor_position()
+            .map(|position| (position, Vec2::new(window.width(), window.height())))
+    });
+
+    let dt = time.delta_seconds();
+
+    for (camera, controller) in controllers.iter() {
+        let RtsCameraController {
+            enabled,
+            pan_sensitivity,
+            edge_scroll_margin,
+            mouse_rotate_sensitivity,
+            key_rotate_sensitivity,
+            mouse_wheel_zoom_sensitivity,
+            ..
+        } = *controller;
+
+        if !enabled {
+            continue;
+        }
+
+        let mut pan = key_pan;
+        if let Some((position, size)) = cursor {
+            pan += edge_scroll_direction(position, size, edge_scroll_margin);
+        }
+        if pan != Vec2::ZERO {
+            events.send(ControlEvent::Pan(
+                camera,
+                pan_sensitivity * dt * pan.normalize(),
+            ));
+        }
+
+        let mut rotate = key_rotate_sensitivity * dt * key_rotate;
+        if any_pressed(&bindings.rotate_drag) {
+            rotate += mouse_rotate_sensitivity * cursor_delta.x;
+        }
+        if rotate != 0.0 {
+            events.send(ControlEvent::Rotate(camera, rotate));
+        }
+
+        if wheel_delta != 0.0 {
+            events.send(ControlEvent::Zoom(
+                camera,
+                -wheel_delta * mouse_wheel_zoom_sensitivity,
+            ));
+        }
+    }
+}
+
+pub fn control_system(
+    mut events: EventReader<ControlEvent>,
+    mut cameras: Query<(Entity, &RtsCameraController, &mut LookTransform, &Transform)>,
+) {
+    // Collect the events so each camera can pick out the ones addressed to it.
+    let events: Vec<&ControlEvent> = events.iter().collect();
+
+    for (camera, controller, mut transform, scene_transform) in cameras.iter_mut() {
+        if !controller.enabled {
+            continue;
+        }
+
+        let mut look_angles = look_angles_or_last_valid(
+            transform.look_direction(),
+            transform.up,
+            scene_transform,
+            -Vec3::Z,
+        );
+        let height = (transform.eye - transform.target).dot(look_angles.up());
+        let mut zoom = zoom_from_height(height, controller);
+
+        // Pan along the ground, relative to the direction the camera is facing.
+        let yaw_rot = look_angles.yaw_rotation();
+        let right = yaw_rot * -Vec3::X;
+        let forward = yaw_rot * Vec3::Z;
+        let pan_scale = height_from_zoom(zoom, controller);
+
+        for event in events.iter().filter(|e| e.camera() == camera) {
+            match event {
+                ControlEvent::Pan(_, delta) => {
+                    transform.target += pan_scale * (delta.x * right + delta.y * forward);
+                }
+                ControlEvent::Rotate(_, delta) => {
+                    look_angles.add_yaw(-delta);
+                }
+                ControlEvent::Zoom(_, delta) => {
+                    zoom = (zoom + delta).clamp(0.0, 1.0);
+                }
+            }
+        }
+
+        if let Some((min, max)) = controller.map_bounds {
+            transform.target = transform.target.max(min).min(max);
+        }
+
+        let target = transform.target;
+        *transform = rts_transform(target, look_angles, zoom, controller);
+    }
+}
+
+/// The pan direction for the cursor at `position` in a window of `size`, with X to the right and Y forward, i.e. up the
+/// screen.
+fn edge_scroll_direction(position: Vec2, size: Vec2, margin: f32) -> Vec2 {
+    if margin <= 0.0 {
+        return Vec2::ZERO;
+    }
+
+    // The cursor position has its origin at the bottom left of the window.
+    let mut direction = Vec2::ZERO;
+    if position.x < margin {
+        direction.x -= 1.0;
+    }
+    if position.x > size.x - margin {
+        direction.x += 1.0;
+    }
+    if position.y < margin {
+        direction.y -= 1.0;
+    }
+    if position.y > size.y - margin {
+        direction.y += 1.0;
+    }
+
+    direction
+}
+
+/// The eye's height at the `zoom` level, which is interpolated exponentially so each step of zooming feels the same.
+fn height_from_zoom(zoom: f32, controller: &RtsCameraController) -> f32 {
+    let min = controller.min_height.max(f32::EPSILON);
+    let max = controller.max_height.max(min);
+
+    min * (max / min).powf(zoom.clamp(0.0, 1.0))
+}
+
+fn zoom_from_height(height: f32, controller: &RtsCameraController) -> f32 {
+    let min = controller.min_height.max(f32::EPSILON);
+    let max = controller.max_height.max(min);
+    if max <= min || height <= 0.0 {
+        return 0.0;
+    }
+
+    ((height / min).ln() / (max / min).ln()).clamp(0.0, 1.0)
+}
+
+fn pitch_from_zoom(zoom: f32, controller: &RtsCameraController) -> f32 {
+    controller.min_pitch + zoom.clamp(0.0, 1.0) * (controller.max_pitch - controller.min_pitch)
+}
+
+/// The camera looking at `target` from the height and pitch of the `zoom` level, facing the heading of `look_angles`.
+fn rts_transform(
+    target: Vec3,
+    mut look_angles: LookAngles,
+    zoom: f32,
+    controller: &RtsCameraController,
+) -> LookTransform {
+    look_angles.set_pitch(-pitch_from_zoom(zoom, controller));
+    let look = look_angles.unit_vector();
+    let up = look_angles.up();
+
+    // The eye is along the look direction from the target, at the height of the zoom level.
+    let height = height_from_zoom(zoom, controller);
+    let sin_pitch = (-look.dot(up)).max(f32::EPSILON);
+    let eye = target - (height / sin_pitch) * look;
+
+    LookTransform::new(eye, target, up)
+}
+
+// ████████╗███████╗███████╗████████╗
+// ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝
+//    ██║   █████╗  ███████╗   ██║
+//    ██║   ██╔══╝  ╚════██║   ██║
+//    ██║   ███████╗███████║   ██║
+//    ╚═╝   ╚══════╝╚══════╝   ╚═╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+    use bevy::app::Events;
+
+    /// A camera looking down -Z at `target` from halfway through the zoom levels, and a stage that runs the `control_system`.
+    fn rts_world(controller: RtsCameraController, target: Vec3) -> (World, SystemStage, Entity) {
+        let mut world = World::default();
+        world.insert_resource(Events::<ControlEvent>::default());
+
+        let look_angles = LookAngles::from_vector(-Vec3::Z).unwrap();
+        let look_transform = rts_transform(target, look_angles, 0.5, &controller);
+        let camera = world
+            .spawn()
+            .insert(controller)
+            .insert(look_transform)
+            .insert(Transform::from(look_transform))
+            .id();
+
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(control_system.system());
+
+        (world, stage, camera)
+    }
+
+    fn send(world: &mut World, event: ControlEvent) {
+        world
+            .get_resource_mut::<Events<ControlEvent>>()
+            .unwrap()
+            .send(event);
+    }
+
+    fn look_transform(world: &World, camera: Entity) -> LookTransform {
+        *world.get::<LookTransform>(camera).unwrap()
+    }
+
+    #[test]
+    fn test_zoom_curve() {
+        let controller = RtsCameraController::default();
+        let look_angles = LookAngles::from_vector(-Vec3::Z).unwrap();
+
+        for &zoom in [0.0, 0.3, 1.0].iter() {
+            let transform = rts_transform(Vec3::ZERO, look_angles, zoom, &controller);
+
+            let height = transform.eye.y - transform.target.y;
+            assert_relative_eq!(height, height_from_zoom(zoom, &controller), epsilon = 1e-3);
+            assert_relative_eq!(zoom_from_height(height, &controller), zoom, epsilon = 1e-4);
+
+            let pitch = (-transform.look_direction().y).asin();
+            assert_relative_eq!(pitch, pitch_from_zoom(zoom, &controller), epsilon = 1e-3);
+
+            // The heading is kept.
+            assert!(transform.look_direction().z < 0.0);
+            assert_relative_eq!(transform.look_direction().x, 0.0, epsilon = 1e-5);
+        }
+
+        assert_relative_eq!(height_from_zoom(0.0, &controller), controller.min_height);
+        assert_relative_eq!(
+            height_from_zoom(1.0, &controller),
+            controller.max_height,
+            epsilon = 1e-3
+        );
+    }
+
+    #[test]
+    fn test_edge_scroll_direction() {
+        let size = Vec2::new(800.0, 600.0);
+
+        assert_eq!(edge_scroll_direction(0.5 * size, size, 10.0), Vec2::ZERO);
+        assert_eq!(
+            edge_scroll_direction(Vec2::new(2.0, 300.0), size, 10.0),
+            -Vec2::X
+        );
+        assert_eq!(
+            edge_scroll_direction(Vec2::new(795.0, 598.0), size, 10.0),
+            Vec2::new(1.0, 1.0)
+        );
+        assert_eq!(
+            edge_scroll_direction(Vec2::new(2.0, 300.0), size, 0.0),
+            Vec2::ZERO
+        );
+    }
+
+    #[test]
+    fn test_pan_is_kept_in_map_bounds() {
+        let controller = RtsCameraController {
+            map_bounds: Some((Vec3::new(-10.0, 0.0, -10.0), Vec3::new(10.0, 0.0, 10.0))),
+            ..Default::default()
+        };
+        let (mut world, mut stage, camera) = rts_world(controller, Vec3::ZERO);
+        let height = look_transform(&world, camera).eye.y;
+
+        // Far to the right and forward, which is -Z while looking down -Z.
+        send(
+            &mut world,
+            ControlEvent::Pan(camera, Vec2::new(100.0, 100.0)),
+        );
+        stage.run(&mut world);
+
+        let transform = look_transform(&world, camera);
+        assert_relative_eq!(transform.target.x, 10.0, epsilon = 1e-4);
+        assert_relative_eq!(transform.target.y, 0.0, epsilon = 1e-4);
+        assert_relative_eq!(transform.target.z, -10.0, epsilon = 1e-4);
+
+        // The view around the clamped target is the same as before panning.
+        assert_relative_eq!(transform.eye.y, height, epsilon = 1e-3);
+        assert_relative_eq!(transform.look_direction().x, 0.0, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_rotate_keeps_target_fixed() {
+        let target = Vec3::new(3.0, 0.0, -2.0);
+        let (mut world, mut stage, camera) = rts_world(RtsCameraController::default(), target);
+        let before = look_transform(&world, camera);
+
+        send(&mut world, ControlEvent::Rotate(camera, 0.5));
+        stage.run(&mut world);
+        let after = look_transform(&world, camera);
+
+        // The eye swings around the center of the view at the same height and distance.
+        assert_relative_eq!(after.target.x, target.x, epsilon = 1e-4);
+        assert_relative_eq!(after.target.y, target.y, epsilon = 1e-4);
+        assert_relative_eq!(after.target.z, target.z, epsilon = 1e-4);
+        assert_relative_eq!(after.eye.y, before.eye.y, epsilon = 1e-3);
+        assert_relative_eq!(after.radius(), before.radius(), epsilon = 1e-3);
+
+        // The heading turned by the rotation.
+        let heading = |t: &LookTransform| {
+            let look = t.look_direction();
+            Vec2::new(look.x, look.z).normalize()
+        };
+        assert_relative_eq!(
+            heading(&before).angle_between(heading(&after)).abs(),
+            0.5,
+            epsilon = 1e-4
+        );
+    }
+}
@@ -0,0 +1,539 @@
+use crate::{
+    controllers::{
+        follow::FollowTarget, look_angles_or_last_valid, ConnectedGamepads, InputBinding,
+    },
+    AngleLimits, LookAngles, LookTransform, LookTransformBundle, Smoother,
+};
+
+use bevy::{
+    app::prelude::*,
+    core::Time,
+    ecs::{bundle::Bundle, prelude::*},
+    input::{
+        gamepad::{GamepadAxis, GamepadButton, GamepadEvent},
+        mouse::MouseMotion,
+        prelude::*,
+    },
+    math::prelude::*,
+    render::{
+        camera::{Camera, CameraProjection, PerspectiveProjection},
+        prelude::*,
+    },
+    transform::components::{GlobalTransform, Transform},
+};
+use serde::{Deserialize, Serialize};
+
+pub struct ShoulderCameraPlugin;
+
+impl Plugin for ShoulderCameraPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<ShoulderCameraBindings>()
+            .add_system(default_input_map.system())
+            .add_system(control_system.system())
+            .add_event::<ControlEvent>();
+    }
+}
+
+#[derive(Bundle)]
+pub struct ShoulderCameraBundle {
+    controller: ShoulderCameraController,
+    follow_target: FollowTarget,
+    shoulder_offset: ShoulderOffset,
+    aim: ShoulderAim,
+    #[bundle]
+    look_transform: LookTransformBundle,
+    #[bundle]
+    perspective: PerspectiveCameraBundle,
+}
+
+impl ShoulderCameraBundle {
+    pub fn new(
+        controller: ShoulderCameraController,
+        follow_target: FollowTarget,
+        mut perspective: PerspectiveCameraBundle,
+        eye: Vec3,
+        target: Vec3,
+        up: Vec3,
+    ) -> Self {
+        // Make sure the transform and projection are consistent with the controller to start.
+        perspective.transform = Transform::from_translation(eye).looking_at(target, up);
+        perspective.perspective_projection.fov = controller.fov;
+
+        Self {
+            controller,
+            follow_target,
+            shoulder_offset: ShoulderOffset::default(),
+            aim: ShoulderAim::default(),
+            look_transform: LookTransformBundle {
+                transform: LookTransform::new(eye, target, up),
+                smoother: Smoother::new_separate(
+                    controller.smoothing_weight,
+                    controller.eye_smoothing_weight,
+                    controller.target_smoothing_weight,
+                ),
+            },
+            perspective,
+        }
+    }
+}
+
+/// An over-the-shoulder camera for shooters. It looks past the side of a `FollowTarget` entity, so the crosshair at the
+/// center of the screen isn't covered by the character.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct ShoulderCameraController {
+    pub enabled: bool,
+    /// Radians per pixel of mouse motion.
+    pub mouse_rotate_sensitivity: Vec2,
+    /// Radians per second with the right stick fully tilted.
+    pub gamepad_rotate_sensitivity: Vec2,
+    /// Stick and trigger inputs below this magnitude are ignored.
+    pub gamepad_dead_zone: f32,
+    /// How far the eye is behind the followed entity.
+    pub distance: f32,
+    /// How far the eye is to the side of the followed entity.
+    pub shoulder_offset: f32,
+    /// The vertical field of view in radians.
+    pub fov: f32,
+    /// The distance while aiming.
+    pub aim_distance: f32,
+    /// The vertical field of view in radians while aiming.
+    pub aim_fov: f32,
+    /// The half-life in seconds of swapping shoulders and of entering or leaving aim mode.
+    pub transition_half_life: f32,
+    /// Limits the angles of the look direction, relative to the up vector.
+    pub angle_limits: AngleLimits,
+    /// The `Smoother` lag weight of the eye and target, between `0.0` and `1.0`, where higher is smoother.
+    pub smoothing_weight: f32,
+    /// Overrides the `smoothing_weight` of the eye.
+    pub eye_smoothing_weight: Option<f32>,
+    /// Overrides the `smoothing_weight` of the target.
+    pub target_smoothing_weight: Option<f32>,
+}
+
+impl Default for ShoulderCameraController {
+    fn default() -> Self {
+        Self {
+            mouse_rotate_sensitivity: Vec2::splat(0.002),
+            gamepad_rotate_sensitivity: Vec2::splat(2.0),
+            gamepad_dead_zone: 0.15,
+            distance: 3.0,
+            shoulder_offset: 0.6,
+            fov: std::f32::consts::PI / 4.0,
+            aim_distance: 1.5,
+            aim_fov: std::f32::consts::PI / 8.0,
+            transition_half_life: 0.08,
+            angle_limits: AngleLimits::default(),
+            smoothing_weight: 0.5,
+            eye_smoothing_weight: None,
+            target_smoothing_weight: None,
+            enabled: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Shoulder {
+    Left,
+    Right,
+}
+
+impl Shoulder {
+    pub fn other(self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+
+    /// `-1.0` for the left shoulder and `1.0` for the right shoulder.
+    pub fn side(self) -> f32 {
+        match self {
+            Self::Left => -1.0,
+            Self::Right => 1.0,
+        }
+    }
+}
+
+/// Which shoulder a `ShoulderCameraController` looks over.
+#[derive(Clone, Copy, Debug)]
+pub struct ShoulderOffset {
+    pub shoulder: Shoulder,
+    /// Moves from `-1.0` (left) to `1.0` (right) when swapping shoulders.
+    pub side: f32,
+}
+
+impl Default for ShoulderOffset {
+    fn default() -> Self {
+        Self {
+            shoulder: Shoulder::Right,
+            side: Shoulder::Right.side(),
+        }
+    }
+}
+
+/// Whether a `ShoulderCameraController` is aiming.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ShoulderAim {
+    pub aiming: bool,
+    /// Moves from `0.0` (not aiming) to `1.0` (aiming) when entering aim mode.
+    pub blend: f32,
+}
+
+/// The inputs read by `shoulder::default_input_map`. Insert this resource before adding the `ShoulderCameraPlugin` to override
+/// the defaults, e.g. after deserializing it from a config file.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct ShoulderCameraBindings {
+    /// Pressed to look over the other shoulder.
+    pub swap_shoulder: InputBinding,
+    /// Held to aim.
+    pub aim: InputBinding,
+}
+
+impl Default for ShoulderCameraBindings {
+    fn default() -> Self {
+        Self {
+            swap_shoulder: InputBinding::Key(KeyCode::Q),
+            aim: InputBinding::Mouse(MouseButton::Right),
+        }
+    }
+}
+
+/// An input for the camera entity it is addressed to.
+pub enum ControlEvent {
+    Rotate(Entity, Vec2),
+    SwapShoulder(Entity),
+    Aim(Entity, bool),
+}
+
+impl ControlEvent {
+    /// The camera entity that should handle this event.
+    pub fn camera(&self) -> Entity {
+        match *self {
+            Self::Rotate(camera, _) | Self::SwapShoulder(camera) | Self::Aim(camera, _) => camera,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn default_input_map(
+    mut events: EventWriter<ControlEvent>,
+    time: Res<Time>,
+    bindings: Res<ShoulderCameraBindings>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    mut gamepad_events: EventReader<GamepadEvent>,
+    mut gamepads: Local<ConnectedGamepads>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepad_button_axes: Res<Axis<GamepadButton>>,
+    controllers: Query<(Entity, &ShoulderCameraController)>,
+) {
+    let mut cursor_delta = Vec2::ZERO;
+    for event in mouse_motion_events.iter() {
+        cursor_delta += event.delta;
+    }
+
+    gamepads.update(&mut gamepad_events);
+
+    let dt = time.delta_seconds();
+
+    for (camera, controller) in controllers.iter() {
+        let ShoulderCameraController {
+            enabled,
+            mouse_rotate_sensitivity,
+            gamepad_rotate_sensitivity,
+            gamepad_dead_zone,
+            ..
+        } = *controller;
+
+        if !enabled {
+            continue;
+        }
+
+        events.send(ControlEvent::Rotate(
+            camera,
+            mouse_rotate_sensitivity * cursor_delta,
+        ));
+
+        let right_stick = gamepads.right_stick(&gamepad_axes, gamepad_dead_zone);
+        if right_stick != Vec2::ZERO {
+            // Pushing the stick up should look up, like moving the mouse up.
+            events.send(ControlEvent::Rotate(
+                camera,
+                gamepad_rotate_sensitivity * dt * Vec2::new(right_stick.x, -right_stick.y),
+            ));
+        }
+
+        if bindings
+            .swap_shoulder
+            .just_pressed(&keyboard, &mouse_buttons)
+        {
+            events.send(ControlEvent::SwapShoulder(camera));
+        }
+
+        let trigger_aim = gamepads.trigger(
+            &gamepad_button_axes,
+            GamepadButtonType::LeftTrigger2,
+            gamepad_dead_zone,
+        ) > 0.5;
+        events.send(ControlEvent::Aim(
+            camera,
+            bindings.aim.pressed(&keyboard, &mouse_buttons) || trigger_aim,
+        ));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn control_system(
+    mut events: EventReader<ControlEvent>,
+    time: Res<Time>,
+    mut cameras: Query<(
+        Entity,
+        &ShoulderCameraController,
+        &FollowTarget,
+        &mut ShoulderOffset,
+        &mut ShoulderAim,
+        &mut LookTransform,
+        &Transform,
+        &mut PerspectiveProjection,
+        Option<&mut Camera>,
+    )>,
+    followed: Query<&GlobalTransform>,
+) {
+    // Collect the events so each camera can pick out the ones addressed to it.
+    let events: Vec<&ControlEvent> = events.iter().collect();
+
+    let dt = time.delta_seconds();
+
+    for (
+        camera,
+        controller,
+        follow_target,
+        mut shoulder_offset,
+        mut aim,
+        mut transform,
+        scene_transform,
+        mut projection,
+        bevy_camera,
+    ) in cameras.iter_mut()
+    {
+        if !controller.enabled {
+            continue;
+        }
+
+        let look_vector = transform.look_direction();
+        let mut look_angles =
+            look_angles_or_last_valid(look_vector, transform.up, scene_transform, -Vec3::Z)
+                .with_limits(controller.angle_limits);
+
+        for event in events.iter().filter(|e| e.camera() == camera) {
+            match event {
+                ControlEvent::Rotate(_, delta) => {
+                    look_angles.add_yaw(-delta.x);
+                    look_angles.add_pitch(-delta.y);
+                }
+                ControlEvent::SwapShoulder(_) => {
+                    shoulder_offset.shoulder = shoulder_offset.shoulder.other();
+                }
+                ControlEvent::Aim(_, aiming) => {
+                    aim.aiming = *aiming;
+                }
+            }
+        }
+
+        shoulder_offset.side = approach(
+            shoulder_offset.side,
+            shoulder_offset.shoulder.side(),
+            dt,
+            controller.transition_half_life,
+        );
+        aim.blend = approach(
+            aim.blend,
+            if aim.aiming { 1.0 } else { 0.0 },
+            dt,
+            controller.transition_half_life,
+        );
+
+        let fov = controller.fov + aim.blend * (controller.aim_fov - controller.fov);
+        if projection.fov != fov {
+            projection.fov = fov;
+
+            // Bevy only recomputes the projection matrix when the window is resized.
+            if let Some(mut bevy_camera) = bevy_camera {
+                bevy_camera.projection_matrix = projection.get_projection_matrix();
+            }
+        }
+
+        // Without the followed entity, there's nothing to look over the shoulder of.
+        let pivot = match followed.get(follow_target.entity) {
+            Ok(followed) => followed.translation + follow_target.offset,
+            Err(_) => continue,
+        };
+        let distance =
+            controller.distance + aim.blend * (controller.aim_distance - controller.distance);
+
+        *transform = shoulder_transform(
+            pivot,
+            &look_angles,
+            distance,
+            shoulder_offset.side * controller.shoulder_offset,
+        );
+    }
+}
+
+/// The camera behind `pivot`, looking in the direction of `look_angles`, and moved to the right by `side_offset`.
+fn shoulder_transform(
+    pivot: Vec3,
+    look_angles: &LookAngles,
+    distance: f32,
+    side_offset: f32,
+) -> LookTransform {
+    let look = look_angles.unit_vector();
+    let up = look_angles.up();
+    let right = look.cross(up).normalize_or_zero();
+
+    let target = pivot + side_offset * right;
+    let eye = target - distance.max(f32::EPSILON) * look;
+
+    LookTransform::new(eye, target, up)
+}
+
+/// Moves `current` towards `desired`, halving the difference every `half_life` seconds.
+fn approach(current: f32, desired: f32, dt: f32, half_life: f32) -> f32 {
+    if half_life <= 0.0 {
+        return desired;
+    }
+
+    desired + (current - desired) * 0.5f32.powf(dt / half_life)
+}
+
+// ████████╗███████╗███████╗████████╗
+// ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝
+//    ██║   █████╗  ███████╗   ██║
+//    ██║   ██╔══╝  ╚════██║   ██║
+//    ██║   ███████╗███████║   ██║
+//    ╚═╝   ╚══════╝╚══════╝   ╚═╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+    use bevy::app::Events;
+
+    /// A camera looking over the shoulder of an entity at the origin, and a stage that runs the `control_system`.
+    fn shoulder_world(controller: ShoulderCameraController) -> (World, SystemStage, Entity) {
+        let mut world = World::default();
+        let mut time = Time::default();
+        time.update();
+        world.insert_resource(time);
+        world.insert_resource(Events::<ControlEvent>::default());
+
+        let followed = world
+            .spawn()
+            .insert(GlobalTransform::from_translation(Vec3::ZERO))
+            .id();
+        let eye = Vec3::new(controller.shoulder_offset, 0.0, controller.distance);
+        let target = Vec3::new(controller.shoulder_offset, 0.0, 0.0);
+        let camera = world
+            .spawn()
+            .insert(controller)
+            .insert(FollowTarget::new(followed, Vec3::ZERO))
+            .insert(ShoulderOffset::default())
+            .insert(ShoulderAim::default())
+            .insert(LookTransform::new(eye, target, Vec3::Y))
+            .insert(Transform::from_translation(eye).looking_at(target, Vec3::Y))
+            .insert(PerspectiveProjection {
+                fov: controller.fov,
+                ..Default::default()
+            })
+            .id();
+
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(control_system.system());
+
+        (world, stage, camera)
+    }
+
+    /// Updates the `Time` for the next frame and returns its delta, which is however long has passed since the last update.
+    fn next_frame_dt(world: &mut World) -> f32 {
+        let mut time = world.get_resource_mut::<Time>().unwrap();
+        time.update();
+        let dt = time.delta_seconds();
+        assert!(dt > 0.0);
+
+        dt
+    }
+
+    fn look_transform(world: &World, camera: Entity) -> LookTransform {
+        *world.get::<LookTransform>(camera).unwrap()
+    }
+
+    #[test]
+    fn test_shoulder_transform() {
+        // Looking down -Z, so the right shoulder is at +X.
+        let look_angles = LookAngles::from_vector(-Vec3::Z).unwrap();
+        let pivot = Vec3::new(0.0, 2.0, 0.0);
+
+        let right = shoulder_transform(pivot, &look_angles, 3.0, 0.5);
+        assert_relative_eq!(right.eye.x, 0.5, epsilon = 1e-5);
+        assert_relative_eq!(right.eye.y, 2.0, epsilon = 1e-5);
+        assert_relative_eq!(right.eye.z, 3.0, epsilon = 1e-5);
+        assert_relative_eq!(right.look_direction().z, -1.0, epsilon = 1e-5);
+
+        let left = shoulder_transform(pivot, &look_angles, 3.0, -0.5);
+        assert_relative_eq!(left.eye.x, -0.5, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_swapping_shoulders_and_aiming_are_smooth() {
+        let controller = ShoulderCameraController::default();
+        let (mut world, mut stage, camera) = shoulder_world(controller);
+
+        // Settle over the right shoulder, looking down -Z, so the right shoulder is at +X.
+        stage.run(&mut world);
+        let transform = look_transform(&world, camera);
+        assert_relative_eq!(
+            transform.target.x,
+            controller.shoulder_offset,
+            epsilon = 1e-5
+        );
+        assert_relative_eq!(transform.radius(), controller.distance, epsilon = 1e-4);
+
+        let mut events = world.get_resource_mut::<Events<ControlEvent>>().unwrap();
+        events.send(ControlEvent::SwapShoulder(camera));
+        events.send(ControlEvent::Aim(camera, true));
+
+        for frame in 1..=10 {
+            // Real time passes between frames, so make each frame one half-life long to close exactly half of the remaining
+            // distance, rather than letting the transition depend on how fast the test runs.
+            let dt = next_frame_dt(&mut world);
+            world
+                .get_mut::<ShoulderCameraController>(camera)
+                .unwrap()
+                .transition_half_life = dt;
+            stage.run(&mut world);
+
+            let remaining = 0.5f32.powi(frame);
+            let side = -1.0 + 2.0 * remaining;
+            let blend = 1.0 - remaining;
+
+            let transform = look_transform(&world, camera);
+            assert_relative_eq!(
+                transform.target.x,
+                side * controller.shoulder_offset,
+                epsilon = 1e-4
+            );
+            assert_relative_eq!(
+                transform.radius(),
+                controller.distance + blend * (controller.aim_distance - controller.distance),
+                epsilon = 1e-4
+            );
+            assert_relative_eq!(
+                world.get::<PerspectiveProjection>(camera).unwrap().fov,
+                controller.fov + blend * (controller.aim_fov - controller.fov),
+                epsilon = 1e-5
+            );
+        }
+    }
+}
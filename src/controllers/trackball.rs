@@ -0,0 +1,510 @@
+use crate::{
+    controllers::{clamp_radius, nonzero_radius, zoom_with_limits, InputBinding, ProjectionBundle},
+    LookTransform, LookTransformBundle, Smoother, SmoothingMode,
+};
+
+use bevy::{
+    app::prelude::*,
+    core::Time,
+    ecs::{bundle::Bundle, prelude::*},
+    input::{
+        mouse::{MouseMotion, MouseWheel},
+        prelude::*,
+    },
+    math::prelude::*,
+    render::{
+        camera::{Camera, CameraProjection, OrthographicProjection},
+        prelude::*,
+    },
+    transform::components::Transform,
+    window::Windows,
+};
+use serde::{Deserialize, Serialize};
+
+pub struct TrackballCameraPlugin;
+
+impl Plugin for TrackballCameraPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<TrackballCameraBindings>()
+            .add_system(default_input_map.system())
+            .add_system(control_system.system())
+            .add_event::<ControlEvent>();
+    }
+}
+
+/// Use a `PerspectiveCameraBundle` or an `OrthographicCameraBundle` for the projection.
+#[derive(Bundle)]
+pub struct TrackballCameraBundle<P: ProjectionBundle = PerspectiveCameraBundle> {
+    controller: TrackballCameraController,
+    #[bundle]
+    look_transform: LookTransformBundle,
+    #[bundle]
+    projection: P,
+}
+
+impl<P: ProjectionBundle> TrackballCameraBundle<P> {
+    pub fn new(
+        controller: TrackballCameraController,
+        mut projection: P,
+        eye: Vec3,
+        target: Vec3,
+        up: Vec3,
+    ) -> Self {
+        // Make sure the transform is consistent with the controller to start.
+        *projection.transform_mut() = Transform::from_translation(eye).looking_at(target, up);
+
+        // Slerp the whole orientation, so the up vector turns along with the eye when tumbling over the top.
+        let mut smoother = Smoother::new_separate(
+            controller.smoothing_weight,
+            controller.eye_smoothing_weight,
+            controller.target_smoothing_weight,
+        );
+        smoother.set_mode(SmoothingMode::Rotation);
+
+        Self {
+            controller,
+            look_transform: LookTransformBundle {
+                transform: LookTransform::new(eye, target, up),
+                smoother,
+            },
+            projection,
+        }
+    }
+}
+
+/// A camera that tumbles freely around its target, for inspecting models. Dragging the mouse turns a virtual sphere around
+/// the target, as if the model were a trackball under the cursor. Unlike the `OrbitCameraController`, the pitch isn't limited,
+/// so the camera can roll over the top of the target; dragging around the edge of the sphere rolls the camera.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct TrackballCameraController {
+    pub enabled: bool,
+    /// Scales the rotation of the virtual sphere. At `1.0`, the point that was grabbed on the sphere stays under the cursor.
+    pub mouse_rotate_sensitivity: f32,
+    /// Units per pixel of mouse motion.
+    pub mouse_translate_sensitivity: Vec2,
+    /// Fraction of the radius per line of mouse wheel scrolling.
+    pub mouse_wheel_zoom_sensitivity: f32,
+    /// Radians per second while a roll binding is held.
+    pub key_roll_sensitivity: f32,
+    /// The closest the eye can get to the target.
+    pub min_radius: f32,
+    /// The furthest the eye can get from the target.
+    pub max_radius: f32,
+    /// The smallest scale of an `OrthographicProjection`, which is zoomed instead of the radius.
+    pub min_orthographic_scale: f32,
+    /// The largest scale of an `OrthographicProjection`, which is zoomed instead of the radius.
+    pub max_orthographic_scale: f32,
+    /// The `Smoother` lag weight of the eye and target, between `0.0` and `1.0`, where higher is smoother.
+    pub smoothing_weight: f32,
+    /// Overrides the `smoothing_weight` of the eye.
+    pub eye_smoothing_weight: Option<f32>,
+    /// Overrides the `smoothing_weight` of the target.
+    pub target_smoothing_weight: Option<f32>,
+}
+
+impl Default for TrackballCameraController {
+    fn default() -> Self {
+        Self {
+            mouse_rotate_sensitivity: 1.0,
+            mouse_translate_sensitivity: Vec2::splat(0.008),
+            mouse_wheel_zoom_sensitivity: 0.15,
+            key_roll_sensitivity: 1.5,
+            min_radius: 0.1,
+            max_radius: 1000.0,
+            min_orthographic_scale: 1e-3,
+            max_orthographic_scale: 100.0,
+            smoothing_weight: 0.8,
+            eye_smoothing_weight: None,
+            target_smoothing_weight: None,
+            enabled: true,
+        }
+    }
+}
+
+/// The inputs read by `trackball::default_input_map`. Insert this resource before adding the `TrackballCameraPlugin` to
+/// override the defaults, e.g. after deserializing it from a config file.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct TrackballCameraBindings {
+    /// Held while dragging the mouse to turn the virtual sphere.
+    pub rotate: InputBinding,
+    /// Held while dragging the mouse to pan.
+    pub pan: InputBinding,
+    /// Held to roll counterclockwise around the look direction.
+    pub roll_left: InputBinding,
+    /// Held to roll clockwise around the look direction.
+    pub roll_right: InputBinding,
+}
+
+impl Default for TrackballCameraBindings {
+    fn default() -> Self {
+        Self {
+            rotate: InputBinding::Mouse(MouseButton::Left),
+            pan: InputBinding::Mouse(MouseButton::Right),
+            roll_left: InputBinding::Key(KeyCode::Q),
+            roll_right: InputBinding::Key(KeyCode::E),
+        }
+    }
+}
+
+/// An input for the camera entity it is addressed to.
+pub enum ControlEvent {
+    /// Turns the virtual sphere by the given rotation, in the camera's local space. The camera turns the opposite way around
+    /// the target.
+    Rotate(Entity, Quat),
+    /// Rolls the camera clockwise around the look direction by the given radians.
+    Roll(Entity, f32),
+    TranslateTarget(Entity, Vec2),
+    Zoom(Entity, f32),
+}
+
+impl ControlEvent {
+    /// The camera entity that should handle this event.
+    pub fn camera(&self) -> Entity {
+        match *self {
+            Self::Rotate(camera, _)
+            | Self::Roll(camera, _)
+            | Self::TranslateTarget(camera, _)
+            | Self::Zoom(camera, _) => camera,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn default_input_map(
+    mut events: EventWriter<ControlEvent>,
+    time: Res<Time>,
+    bindings: Res<TrackballCameraBindings>,
+    windows: Res<Windows>,
+    mut mouse_wheel_reader: EventReader<MouseWheel>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    keyboard: Res<Input<KeyCode>>,
+    mut last_sphere_point: Local<Option<Vec3>>,
+    controllers: Query<(Entity, &TrackballCameraController)>,
+) {
+    let mut cursor_delta = Vec2::ZERO;
+    for event in mouse_motion_events.iter() {
+        cursor_delta += event.delta;
+    }
+
+    let wheel_deltas: Vec<f32> = mouse_wheel_reader.iter().map(|event| event.y).collect();
+
+    // Track the point under the cursor on the virtual sphere while the rotate binding is held.
+    let sphere_point = if bindings.rotate.pressed(&keyboard, &mouse_buttons) {
+        windows.get_primary().and_then(|window| {
+            let window_size = Vec2::new(window.width(), window.height());
+            window
+                .cursor_position()
+                .map(|cursor| virtual_sphere_point(cursor, window_size))
+        })
+    } else {
+        None
+    };
+    let sphere_drag = last_sphere_point.zip(sphere_point);
+    *last_sphere_point = sphere_point;
+
+    let mut roll_direction = 0.0;
+    if bindings.roll_left.pressed(&keyboard, &mouse_buttons) {
+        roll_direction -= 1.0;
+    }
+    if bindings.roll_right.pressed(&keyboard, &mouse_buttons) {
+        roll_direction += 1.0;
+    }
+
+    let dt = time.delta_seconds();
+
+    for (camera, controller) in controllers.iter() {
+        let TrackballCameraController {
+            enabled,
+            mouse_rotate_sensitivity,
+            mouse_translate_sensitivity,
+            mouse_wheel_zoom_sensitivity,
+            key_roll_sensitivity,
+            ..
+        } = *controller;
+
+        if !enabled {
+            continue;
+        }
+
+        if let Some((from, to)) = sphere_drag {
+            let rotation = sphere_rotation(from, to, mouse_rotate_sensitivity);
+            if rotation != Quat::IDENTITY {
+                events.send(ControlEvent::Rotate(camera, rotation));
+            }
+        }
+
+        if roll_direction != 0.0 {
+            events.send(ControlEvent::Roll(
+                camera,
+                key_roll_sensitivity * roll_direction * dt,
+            ));
+        }
+
+        if bindings.pan.pressed(&keyboard, &mouse_buttons) {
+            events.send(ControlEvent::TranslateTarget(
+                camera,
+                mouse_translate_sensitivity * cursor_delta,
+            ));
+        }
+
+        let mut scalar = 1.0;
+        for delta in wheel_deltas.iter() {
+            scalar *= 1.0 + -delta * mouse_wheel_zoom_sensitivity;
+        }
+        events.send(ControlEvent::Zoom(camera, scalar));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn control_system(
+    mut events: EventReader<ControlEvent>,
+    time: Res<Time>,
+    mut cameras: Query<(
+        Entity,
+        &TrackballCameraController,
+        &mut LookTransform,
+        &Transform,
+        Option<&mut OrthographicProjection>,
+        Option<&mut Camera>,
+    )>,
+) {
+    // Collect the events so each camera can pick out the ones addressed to it.
+    let events: Vec<&ControlEvent> = events.iter().collect();
+
+    let dt = time.delta_seconds();
+
+    for (camera, controller, mut transform, scene_transform, orthographic, bevy_camera) in
+        cameras.iter_mut()
+    {
+        if !controller.enabled {
+            continue;
+        }
+
+        // Rotate the whole orientation rather than look angles, so nothing stops the camera at the poles.
+        let mut rotation = transform.rotation().unwrap_or(scene_transform.rotation);
+        // Measure the radius before panning moves the target away from the eye.
+        let radius = nonzero_radius(transform.radius());
+        let mut radius_scalar = 1.0;
+
+        for event in events.iter().filter(|e| e.camera() == camera) {
+            match event {
+                ControlEvent::Rotate(_, sphere_rotation) => {
+                    rotation *= sphere_rotation.inverse();
+                }
+                ControlEvent::Roll(_, angle) => {
+                    rotation *= Quat::from_rotation_z(-angle);
+                }
+                ControlEvent::TranslateTarget(_, delta) => {
+                    let right_dir = rotation * -Vec3::X;
+                    let up_dir = rotation * Vec3::Y;
+                    transform.target += delta.x * right_dir + delta.y * up_dir;
+                }
+                ControlEvent::Zoom(_, scalar) => {
+                    radius_scalar *= scalar;
+                }
+            }
+        }
+
+        // Renormalize so rounding errors don't accumulate over many small rotations.
+        let rotation = rotation.normalize();
+        let radius = if let Some(mut orthographic) = orthographic {
+            // Moving the eye doesn't change the size of an orthographic view, so zoom by scaling the projection instead.
+            let scale = zoom_with_limits(
+                orthographic.scale,
+                radius_scalar,
+                dt,
+                controller.min_orthographic_scale,
+                controller.max_orthographic_scale,
+                None,
+            );
+            if scale != orthographic.scale {
+                orthographic.scale = scale;
+
+                // Bevy only recomputes the projection matrix when the window is resized.
+                if let Some(mut bevy_camera) = bevy_camera {
+                    bevy_camera.projection_matrix = orthographic.get_projection_matrix();
+                }
+            }
+            clamp_radius(radius, controller.min_radius, controller.max_radius)
+        } else {
+            clamp_radius(
+                radius * radius_scalar,
+                controller.min_radius,
+                controller.max_radius,
+            )
+        };
+        transform.eye = transform.target + radius * (rotation * Vec3::Z);
+        transform.up = rotation * Vec3::Y;
+    }
+}
+
+/// Maps the `cursor` position in a window of `window_size` logical pixels onto a unit sphere centered in the window, in the
+/// camera's local space. The sphere fits the smaller window dimension, and cursor positions outside of it are mapped onto its
+/// rim, where dragging rolls around the look direction.
+fn virtual_sphere_point(cursor: Vec2, window_size: Vec2) -> Vec3 {
+    let half_extent = 0.5 * window_size.x.min(window_size.y).max(1.0);
+    let point = (cursor - 0.5 * window_size) / half_extent;
+
+    let length_squared = point.length_squared();
+    if length_squared <= 1.0 {
+        point.extend((1.0 - length_squared).sqrt())
+    } else {
+        (point / length_squared.sqrt()).extend(0.0)
+    }
+}
+
+/// The rotation of the virtual sphere that takes the point `from` to the point `to`, with its angle scaled by `sensitivity`.
+fn sphere_rotation(from: Vec3, to: Vec3, sensitivity: f32) -> Quat {
+    let axis = from.cross(to).normalize_or_zero();
+    if axis == Vec3::ZERO {
+        return Quat::IDENTITY;
+    }
+
+    Quat::from_axis_angle(axis, sensitivity * from.angle_between(to))
+}
+
+// ████████╗███████╗███████╗████████╗
+// ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝
+//    ██║   █████╗  ███████╗   ██║
+//    ██║   ██╔══╝  ╚════██║   ██║
+//    ██║   ███████╗███████║   ██║
+//    ╚═╝   ╚══════╝╚══════╝   ╚═╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+    use bevy::app::Events;
+
+    /// A camera 5 units in front of the origin, and a stage that runs the `control_system`.
+    fn trackball_world() -> (World, SystemStage, Entity) {
+        let mut world = World::default();
+        world.insert_resource(Time::default());
+        world.insert_resource(Events::<ControlEvent>::default());
+
+        let look_transform = LookTransform::new(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y);
+        let camera = world
+            .spawn()
+            .insert(TrackballCameraController::default())
+            .insert(look_transform)
+            .insert(Transform::from(look_transform))
+            .id();
+
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(control_system.system());
+
+        (world, stage, camera)
+    }
+
+    fn send(world: &mut World, event: ControlEvent) {
+        world
+            .get_resource_mut::<Events<ControlEvent>>()
+            .unwrap()
+            .send(event);
+    }
+
+    #[test]
+    fn test_virtual_sphere_point() {
+        let window_size = Vec2::new(800.0, 600.0);
+
+        let center = virtual_sphere_point(Vec2::new(400.0, 300.0), window_size);
+        assert_relative_eq!(center.z, 1.0);
+
+        let inside = virtual_sphere_point(Vec2::new(550.0, 300.0), window_size);
+        assert_relative_eq!(inside.x, 0.5);
+        assert_relative_eq!(inside.length(), 1.0, epsilon = 1e-6);
+
+        let outside = virtual_sphere_point(Vec2::new(400.0, 0.0), window_size);
+        assert_relative_eq!(outside.y, -1.0);
+        assert_relative_eq!(outside.z, 0.0);
+    }
+
+    #[test]
+    fn test_sphere_rotation_moves_grabbed_point_to_cursor() {
+        let window_size = Vec2::new(800.0, 600.0);
+        let from = virtual_sphere_point(Vec2::new(400.0, 300.0), window_size);
+        let to = virtual_sphere_point(Vec2::new(500.0, 350.0), window_size);
+
+        let moved = sphere_rotation(from, to, 1.0) * from;
+        assert_relative_eq!(moved.x, to.x, epsilon = 1e-5);
+        assert_relative_eq!(moved.y, to.y, epsilon = 1e-5);
+        assert_relative_eq!(moved.z, to.z, epsilon = 1e-5);
+
+        assert_eq!(sphere_rotation(from, from, 1.0), Quat::IDENTITY);
+    }
+
+    #[test]
+    fn test_tumbles_over_the_top() {
+        let (mut world, mut stage, camera) = trackball_world();
+
+        // Dragging the sphere down repeatedly tumbles the camera over the top, without stopping at the pole.
+        let quarter_turn = sphere_rotation(Vec3::Z, -Vec3::Y, 1.0);
+
+        send(&mut world, ControlEvent::Rotate(camera, quarter_turn));
+        stage.run(&mut world);
+        let transform = world.get::<LookTransform>(camera).unwrap();
+        assert_relative_eq!(transform.eye.y, 5.0, epsilon = 1e-4);
+        assert_relative_eq!(transform.eye.z, 0.0, epsilon = 1e-4);
+
+        send(&mut world, ControlEvent::Rotate(camera, quarter_turn));
+        stage.run(&mut world);
+        let transform = world.get::<LookTransform>(camera).unwrap();
+        assert_relative_eq!(transform.eye.z, -5.0, epsilon = 1e-4);
+        assert_relative_eq!(transform.up.y, -1.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_pan_keeps_radius() {
+        let (mut world, mut stage, camera) = trackball_world();
+
+        send(
+            &mut world,
+            ControlEvent::TranslateTarget(camera, Vec2::new(1.0, 2.0)),
+        );
+        stage.run(&mut world);
+
+        let transform = world.get::<LookTransform>(camera).unwrap();
+        assert_relative_eq!(transform.target.x, -1.0, epsilon = 1e-4);
+        assert_relative_eq!(transform.target.y, 2.0, epsilon = 1e-4);
+        assert_relative_eq!(transform.radius(), 5.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_orthographic_zoom_scales_projection() {
+        let (mut world, mut stage, camera) = trackball_world();
+        let mut projection = OrthographicProjection::default();
+        projection.update(800.0, 600.0);
+        world
+            .entity_mut(camera)
+            .insert(projection)
+            .insert(Camera::default());
+
+        send(&mut world, ControlEvent::Zoom(camera, 2.0));
+        stage.run(&mut world);
+
+        // The view gets bigger, while the eye stays where it was.
+        let projection = world.get::<OrthographicProjection>(camera).unwrap();
+        assert_relative_eq!(projection.scale, 2.0, epsilon = 1e-5);
+        assert_eq!(
+            world.get::<Camera>(camera).unwrap().projection_matrix,
+            projection.get_projection_matrix()
+        );
+        assert_relative_eq!(
+            world.get::<LookTransform>(camera).unwrap().radius(),
+            5.0,
+            epsilon = 1e-4
+        );
+
+        // Zooming out past the limit stops at it.
+        send(&mut world, ControlEvent::Zoom(camera, 1000.0));
+        stage.run(&mut world);
+        let controller = TrackballCameraController::default();
+        assert_relative_eq!(
+            world.get::<OrthographicProjection>(camera).unwrap().scale,
+            controller.max_orthographic_scale,
+            epsilon = 1e-3
+        );
+    }
+}
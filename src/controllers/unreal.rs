@@ -1,9 +1,17 @@
-use crate::{LookAngles, LookTransform, LookTransformBundle, Smoother};
+use crate::{
+    controllers::{
+        look_angles_or_last_valid, nonzero_radius, ConnectedGamepads, InputBinding,
+        ProjectionBundle,
+    },
+    AngleLimits, LookTransform, LookTransformBundle, Smoother,
+};
 
 use bevy::{
     app::prelude::*,
+    core::Time,
     ecs::{bundle::Bundle, prelude::*},
     input::{
+        gamepad::{GamepadAxis, GamepadButton, GamepadEvent},
         mouse::{MouseMotion, MouseWheel},
         prelude::*,
     },
@@ -17,50 +25,79 @@ pub struct UnrealCameraPlugin;
 
 impl Plugin for UnrealCameraPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_system(default_input_map.system())
+        app.init_resource::<UnrealCameraBindings>()
+            .add_system(default_input_map.system())
             .add_system(control_system.system())
             .add_event::<ControlEvent>();
     }
 }
 
+/// Use a `PerspectiveCameraBundle` or an `OrthographicCameraBundle` for the projection.
 #[derive(Bundle)]
-pub struct UnrealCameraBundle {
+pub struct UnrealCameraBundle<P: ProjectionBundle = PerspectiveCameraBundle> {
     controller: UnrealCameraController,
     #[bundle]
     look_transform: LookTransformBundle,
     #[bundle]
-    perspective: PerspectiveCameraBundle,
+    projection: P,
 }
 
-impl UnrealCameraBundle {
+impl<P: ProjectionBundle> UnrealCameraBundle<P> {
     pub fn new(
         controller: UnrealCameraController,
-        mut perspective: PerspectiveCameraBundle,
+        mut projection: P,
         eye: Vec3,
         target: Vec3,
+        up: Vec3,
     ) -> Self {
         // Make sure the transform is consistent with the controller to start.
-        perspective.transform = Transform::from_translation(eye).looking_at(target, Vec3::Y);
+        *projection.transform_mut() = Transform::from_translation(eye).looking_at(target, up);
 
         Self {
             controller,
             look_transform: LookTransformBundle {
-                transform: LookTransform { eye, target },
-                smoother: Smoother::new(controller.smoothing_weight),
+                transform: LookTransform::new(eye, target, up),
+                smoother: Smoother::new_separate(
+                    controller.smoothing_weight,
+                    controller.eye_smoothing_weight,
+                    controller.target_smoothing_weight,
+                ),
             },
-            perspective,
+            projection,
         }
     }
 }
 
 /// A camera controlled with the mouse in the same way as Unreal Engine's viewport controller.
+///
+/// Mouse locomotion is driven by the distance the mouse moved since the last frame, which already adds up to the same total at
+/// any frame rate, so the mouse and trackpad sensitivities are per pixel or line rather than per second. Only the held gamepad
+/// inputs are scaled by the frame time.
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub struct UnrealCameraController {
     pub enabled: bool,
+    /// Radians per pixel of mouse motion.
     pub mouse_rotate_sensitivity: Vec2,
+    /// Units per pixel of mouse motion.
     pub mouse_translate_sensitivity: Vec2,
+    /// Units per line of trackpad scrolling.
     pub trackpad_translate_sensitivity: Vec2,
+    /// Radians per second with a stick fully tilted. The left stick turns, the right stick rotates freely.
+    pub gamepad_rotate_sensitivity: Vec2,
+    /// Units per second moving forward with the left stick fully tilted.
+    pub gamepad_locomotion_sensitivity: f32,
+    /// Units per second moving vertically with a trigger fully pressed.
+    pub gamepad_translate_sensitivity: f32,
+    /// Stick and trigger inputs below this magnitude are ignored.
+    pub gamepad_dead_zone: f32,
+    /// Limits the angles of the look direction, relative to the up vector.
+    pub angle_limits: AngleLimits,
+    /// The `Smoother` lag weight of the eye and target, between `0.0` and `1.0`, where higher is smoother.
     pub smoothing_weight: f32,
+    /// Overrides the `smoothing_weight` of the eye.
+    pub eye_smoothing_weight: Option<f32>,
+    /// Overrides the `smoothing_weight` of the target.
+    pub target_smoothing_weight: Option<f32>,
 }
 
 impl Default for UnrealCameraController {
@@ -70,125 +107,222 @@ impl Default for UnrealCameraController {
             mouse_rotate_sensitivity: Vec2::splat(0.002),
             mouse_translate_sensitivity: Vec2::splat(0.1),
             trackpad_translate_sensitivity: Vec2::splat(0.1),
+            gamepad_rotate_sensitivity: Vec2::splat(2.0),
+            gamepad_locomotion_sensitivity: 6.0,
+            gamepad_translate_sensitivity: 6.0,
+            gamepad_dead_zone: 0.15,
+            angle_limits: AngleLimits::default(),
             smoothing_weight: 0.9,
+            eye_smoothing_weight: None,
+            target_smoothing_weight: None,
         }
     }
 }
 
+/// The inputs read by `unreal::default_input_map`. Insert this resource before adding the `UnrealCameraPlugin` to override
+/// the defaults, e.g. after deserializing it from a config file. Holding both bindings pans the camera.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct UnrealCameraBindings {
+    /// Held while dragging the mouse for locomotion.
+    pub locomotion: InputBinding,
+    /// Held while dragging the mouse to rotate.
+    pub rotate: InputBinding,
+}
+
+impl Default for UnrealCameraBindings {
+    fn default() -> Self {
+        Self {
+            locomotion: InputBinding::Mouse(MouseButton::Left),
+            rotate: InputBinding::Mouse(MouseButton::Right),
+        }
+    }
+}
+
+/// An input for the camera entity it is addressed to.
 pub enum ControlEvent {
-    Locomotion(Vec2),
-    Rotate(Vec2),
-    TranslateEye(Vec2),
+    Locomotion(Entity, Vec2),
+    Rotate(Entity, Vec2),
+    TranslateEye(Entity, Vec2),
+}
+
+impl ControlEvent {
+    /// The camera entity that should handle this event.
+    pub fn camera(&self) -> Entity {
+        match *self {
+            Self::Locomotion(camera, _)
+            | Self::Rotate(camera, _)
+            | Self::TranslateEye(camera, _) => camera,
+        }
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn default_input_map(
     mut events: EventWriter<ControlEvent>,
+    time: Res<Time>,
+    bindings: Res<UnrealCameraBindings>,
     mut mouse_wheel_reader: EventReader<MouseWheel>,
     mut mouse_motion_events: EventReader<MouseMotion>,
+    keyboard: Res<Input<KeyCode>>,
     mouse_buttons: Res<Input<MouseButton>>,
-    controllers: Query<&UnrealCameraController>,
+    mut gamepad_events: EventReader<GamepadEvent>,
+    mut gamepads: Local<ConnectedGamepads>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepad_button_axes: Res<Axis<GamepadButton>>,
+    controllers: Query<(Entity, &UnrealCameraController)>,
 ) {
-    // Can only control one camera at a time.
-    let controller = if let Some(controller) = controllers.iter().next() {
-        controller
-    } else {
-        return;
-    };
-    let UnrealCameraController {
-        enabled,
-        mouse_translate_sensitivity,
-        mouse_rotate_sensitivity,
-        trackpad_translate_sensitivity,
-        ..
-    } = *controller;
-
-    if !enabled {
-        return;
-    }
-
     let mut cursor_delta = Vec2::ZERO;
     for event in mouse_motion_events.iter() {
         cursor_delta += event.delta;
     }
 
-    match (
-        mouse_buttons.pressed(MouseButton::Left),
-        mouse_buttons.pressed(MouseButton::Right),
-    ) {
-        (true, true) => {
-            events.send(ControlEvent::TranslateEye(
-                mouse_translate_sensitivity * cursor_delta,
-            ));
+    // On Mac, mouse wheel is the trackpad, treated the same as both mouse buttons down.
+    let mut trackpad_delta = Vec2::ZERO;
+    for event in mouse_wheel_reader.iter() {
+        trackpad_delta.x += event.x;
+        trackpad_delta.y += event.y;
+    }
+
+    gamepads.update(&mut gamepad_events);
+
+    let dt = time.delta_seconds();
+
+    for (camera, controller) in controllers.iter() {
+        let UnrealCameraController {
+            enabled,
+            mouse_translate_sensitivity,
+            mouse_rotate_sensitivity,
+            trackpad_translate_sensitivity,
+            gamepad_rotate_sensitivity,
+            gamepad_locomotion_sensitivity,
+            gamepad_translate_sensitivity,
+            gamepad_dead_zone,
+            ..
+        } = *controller;
+
+        if !enabled {
+            continue;
         }
-        (true, false) => {
-            events.send(ControlEvent::Locomotion(Vec2::new(
-                mouse_rotate_sensitivity.x * cursor_delta.x,
-                mouse_translate_sensitivity.y * cursor_delta.y,
-            )));
+
+        match (
+            bindings.locomotion.pressed(&keyboard, &mouse_buttons),
+            bindings.rotate.pressed(&keyboard, &mouse_buttons),
+        ) {
+            (true, true) => {
+                events.send(ControlEvent::TranslateEye(
+                    camera,
+                    mouse_translate_sensitivity * cursor_delta,
+                ));
+            }
+            (true, false) => {
+                events.send(ControlEvent::Locomotion(
+                    camera,
+                    Vec2::new(
+                        mouse_rotate_sensitivity.x * cursor_delta.x,
+                        mouse_translate_sensitivity.y * cursor_delta.y,
+                    ),
+                ));
+            }
+            (false, true) => {
+                events.send(ControlEvent::Rotate(
+                    camera,
+                    mouse_rotate_sensitivity * cursor_delta,
+                ));
+            }
+            _ => (),
         }
-        (false, true) => {
+
+        events.send(ControlEvent::TranslateEye(
+            camera,
+            trackpad_translate_sensitivity * trackpad_delta,
+        ));
+
+        // Sticks are mapped to act like dragging the mouse in the direction of the stick.
+        let left_stick = gamepads.left_stick(&gamepad_axes, gamepad_dead_zone);
+        if left_stick != Vec2::ZERO {
+            events.send(ControlEvent::Locomotion(
+                camera,
+                dt * Vec2::new(
+                    gamepad_rotate_sensitivity.x * left_stick.x,
+                    gamepad_locomotion_sensitivity * -left_stick.y,
+                ),
+            ));
+        }
+
+        let right_stick = gamepads.right_stick(&gamepad_axes, gamepad_dead_zone);
+        if right_stick != Vec2::ZERO {
             events.send(ControlEvent::Rotate(
-                mouse_rotate_sensitivity * cursor_delta,
+                camera,
+                gamepad_rotate_sensitivity * dt * Vec2::new(right_stick.x, -right_stick.y),
             ));
         }
-        _ => (),
-    }
 
-    // On Mac, mouse wheel is the trackpad, treated the same as both mouse buttons down.
-    let mut trackpad_delta = Vec2::ZERO;
-    for event in mouse_wheel_reader.iter() {
-        trackpad_delta.x += event.x;
-        trackpad_delta.y += event.y;
+        let up = gamepads.trigger(
+            &gamepad_button_axes,
+            GamepadButtonType::RightTrigger2,
+            gamepad_dead_zone,
+        ) - gamepads.trigger(
+            &gamepad_button_axes,
+            GamepadButtonType::LeftTrigger2,
+            gamepad_dead_zone,
+        );
+        if up != 0.0 {
+            events.send(ControlEvent::TranslateEye(
+                camera,
+                Vec2::new(0.0, -gamepad_translate_sensitivity * dt * up),
+            ));
+        }
     }
-    events.send(ControlEvent::TranslateEye(
-        trackpad_translate_sensitivity * trackpad_delta,
-    ));
 }
 
 pub fn control_system(
     mut events: EventReader<ControlEvent>,
-    mut cameras: Query<(&UnrealCameraController, &mut LookTransform)>,
+    mut cameras: Query<(
+        Entity,
+        &UnrealCameraController,
+        &mut LookTransform,
+        &Transform,
+    )>,
 ) {
-    // Can only control one camera at a time.
-    let (controller, mut transform) =
-        if let Some((controller, transform)) = cameras.iter_mut().next() {
-            (controller, transform)
-        } else {
-            return;
-        };
-
-    if controller.enabled {
+    // Collect the events so each camera can pick out the ones addressed to it.
+    let events: Vec<&ControlEvent> = events.iter().collect();
+
+    for (camera, controller, mut transform, scene_transform) in cameras.iter_mut() {
+        if !controller.enabled {
+            continue;
+        }
+
         let look_vector = transform.look_direction();
-        let mut look_angles = LookAngles::from_vector(look_vector);
-        let forward_vector = Vec3::new(look_vector.x, 0.0, look_vector.z).normalize();
+        let mut look_angles =
+            look_angles_or_last_valid(look_vector, transform.up, scene_transform, -Vec3::Z)
+                .with_limits(controller.angle_limits);
 
-        let yaw_rot = Quat::from_axis_angle(Vec3::Y, look_angles.get_yaw());
+        let yaw_rot = look_angles.yaw_rotation();
         let rot_x = yaw_rot * Vec3::X;
         let rot_y = yaw_rot * Vec3::Y;
+        let forward_vector = yaw_rot * Vec3::Z;
 
-        for event in events.iter() {
+        for event in events.iter().filter(|e| e.camera() == camera) {
             match event {
-                ControlEvent::Locomotion(delta) => {
-                    // Translates forward/backward and rotates about the Y axis.
+                ControlEvent::Locomotion(_, delta) => {
+                    // Translates forward/backward and rotates about the up axis.
                     look_angles.add_yaw(-delta.x);
                     transform.eye -= delta.y * forward_vector;
                 }
-                ControlEvent::Rotate(delta) => {
+                ControlEvent::Rotate(_, delta) => {
                     // Rotates with pitch and yaw.
                     look_angles.add_yaw(-delta.x);
                     look_angles.add_pitch(-delta.y);
                 }
-                ControlEvent::TranslateEye(delta) => {
-                    // Translates up/down (Y) and left/right (X).
+                ControlEvent::TranslateEye(_, delta) => {
+                    // Translates up/down and left/right.
                     transform.eye -= delta.x * rot_x + delta.y * rot_y;
                 }
             }
         }
 
-        look_angles.assert_not_looking_up();
-
-        transform.target = transform.eye + transform.radius() * look_angles.unit_vector();
-    } else {
-        events.iter(); // Drop the events.
+        transform.target =
+            transform.eye + nonzero_radius(transform.radius()) * look_angles.unit_vector();
+        transform.up = look_angles.up();
     }
 }
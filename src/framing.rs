@@ -0,0 +1,387 @@
+use crate::{LookTransform, Smoother, SMOOTHER_REFERENCE_FRAME_SECONDS};
+
+use bevy::{
+    app::prelude::*,
+    core::Time,
+    ecs::prelude::*,
+    math::prelude::*,
+    render::camera::{Camera, CameraProjection, OrthographicProjection, PerspectiveProjection},
+    utils::HashMap,
+};
+use serde::{Deserialize, Serialize};
+
+/// A volume in world space that a camera can be framed around.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Bounds {
+    Sphere { center: Vec3, radius: f32 },
+    Aabb { min: Vec3, max: Vec3 },
+}
+
+impl Bounds {
+    /// The smallest AABB containing all of the `points`, or `None` if there are no points.
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let (min, max) = points.fold((first, first), |(min, max), p| (min.min(p), max.max(p)));
+
+        Some(Self::Aabb { min, max })
+    }
+
+    pub fn center(&self) -> Vec3 {
+        match *self {
+            Self::Sphere { center, .. } => center,
+            Self::Aabb { min, max } => 0.5 * (min + max),
+        }
+    }
+
+    /// The radius of the smallest sphere around `Bounds::center` that contains the bounds.
+    pub fn radius(&self) -> f32 {
+        match *self {
+            Self::Sphere { radius, .. } => radius.abs(),
+            Self::Aabb { min, max } => 0.5 * (max - min).length(),
+        }
+    }
+}
+
+/// Asks the `frame_system` to move a camera so the `bounds` fit in its view, keeping its look direction. The new
+/// `LookTransform` is animated by the camera's `Smoother`, if it has one. The scale of an orthographic camera eases along
+/// with it, at the `Smoother`'s eye lag weight; without a `Smoother`, both jump straight to the framed view.
+#[derive(Clone, Copy, Debug)]
+pub struct FrameEvent {
+    pub camera: Entity,
+    pub bounds: Bounds,
+    /// Extra space around the bounds, as a fraction of their radius.
+    pub padding: f32,
+}
+
+/// Handles `FrameEvent`s for cameras with a `LookTransform` and either a `PerspectiveProjection` or an
+/// `OrthographicProjection`. Perspective cameras move closer or further away, while orthographic cameras change their
+/// projection scale.
+#[allow(clippy::type_complexity)]
+pub fn frame_system(
+    mut events: EventReader<FrameEvent>,
+    time: Res<Time>,
+    // The scale that each orthographic camera is easing towards, and the scale that was set last, to notice when something
+    // else, like a controller, changes it.
+    mut animations: Local<HashMap<Entity, (f32, f32)>>,
+    mut cameras: Query<(
+        &mut LookTransform,
+        Option<&Smoother>,
+        Option<&PerspectiveProjection>,
+        Option<&mut OrthographicProjection>,
+        Option<&mut Camera>,
+    )>,
+) {
+    for event in events.iter() {
+        let (mut transform, smoother, perspective, orthographic, camera) =
+            if let Ok(camera) = cameras.get_mut(event.camera) {
+                camera
+            } else {
+                continue;
+            };
+
+        if let Some(perspective) = perspective {
+            *transform = frame_perspective(
+                &transform,
+                &event.bounds,
+                event.padding,
+                perspective.fov,
+                perspective.aspect_ratio,
+            );
+        } else if let Some(mut orthographic) = orthographic {
+            let (framed, scale) =
+                frame_orthographic(&transform, &event.bounds, event.padding, &orthographic);
+            *transform = framed;
+
+            if smoother.is_some() {
+                animations.insert(event.camera, (scale, orthographic.scale));
+            } else {
+                animations.remove(&event.camera);
+                set_orthographic_scale(&mut orthographic, camera, scale);
+            }
+        }
+    }
+
+    // Zooming an orthographic view is what moving the eye does for a perspective one, so ease at the eye's lag weight.
+    let dt = time.delta_seconds();
+    animations.retain(|&entity, (desired, last)| {
+        let (smoother, mut orthographic, camera) = match cameras.get_mut(entity) {
+            Ok((_, Some(smoother), _, Some(orthographic), camera)) => {
+                (smoother, orthographic, camera)
+            }
+            _ => return false,
+        };
+
+        // Let whatever changed the scale in the meantime have the last word.
+        if orthographic.scale != *last {
+            return false;
+        }
+
+        let scale = smooth_scale(orthographic.scale, *desired, smoother.eye_lag_weight(), dt);
+        set_orthographic_scale(&mut orthographic, camera, scale);
+        *last = scale;
+
+        scale != *desired
+    });
+}
+
+fn set_orthographic_scale(
+    orthographic: &mut OrthographicProjection,
+    camera: Option<Mut<Camera>>,
+    scale: f32,
+) {
+    orthographic.scale = scale;
+
+    // Bevy only recomputes the projection matrix when the window is resized.
+    if let Some(mut camera) = camera {
+        camera.projection_matrix = orthographic.get_projection_matrix();
+    }
+}
+
+/// Exponentially smooths the scale in log space, so zooming in looks as fast as zooming out.
+fn smooth_scale(current: f32, desired: f32, lag_weight: f32, dt: f32) -> f32 {
+    if current <= 0.0 || desired <= 0.0 {
+        return desired;
+    }
+
+    let lag = lag_weight
+        .clamp(0.0, 1.0)
+        .powf(dt / SMOOTHER_REFERENCE_FRAME_SECONDS);
+    let scale = (desired.ln() + lag * (current.ln() - desired.ln())).exp();
+
+    // Stop once the change is invisible, so the projection isn't updated forever.
+    if (scale / desired - 1.0).abs() < 1e-4 {
+        desired
+    } else {
+        scale
+    }
+}
+
+/// Moves `transform` along its look direction so the `bounds` fit in a perspective view with the vertical field of view
+/// `fov` and `aspect_ratio`, and the target is at the center of the bounds.
+pub fn frame_perspective(
+    transform: &LookTransform,
+    bounds: &Bounds,
+    padding: f32,
+    fov: f32,
+    aspect_ratio: f32,
+) -> LookTransform {
+    // The bounds must fit in the narrower of the vertical and horizontal fields of view.
+    let half_fov = 0.5 * fov;
+    let half_horizontal_fov = (half_fov.tan() * aspect_ratio).atan();
+    let half_fov = half_fov.min(half_horizontal_fov);
+
+    let radius = padded_radius(bounds, padding);
+    let distance = radius / half_fov.sin().max(f32::EPSILON);
+
+    look_at_center(transform, bounds, distance)
+}
+
+/// Moves `transform` so its target is at the center of the `bounds`, and returns the scale for the orthographic `projection`
+/// that fits the bounds in the view.
+pub fn frame_orthographic(
+    transform: &LookTransform,
+    bounds: &Bounds,
+    padding: f32,
+    projection: &OrthographicProjection,
+) -> (LookTransform, f32) {
+    let radius = padded_radius(bounds, padding);
+
+    // The projection's extents are multiplied by the scale.
+    let half_extent =
+        0.5 * (projection.right - projection.left).min(projection.top - projection.bottom);
+    let scale = if half_extent > 0.0 {
+        radius / half_extent
+    } else {
+        projection.scale
+    };
+
+    // The distance doesn't change the size of the view, but the bounds still need to be in front of the eye.
+    let distance = transform.radius().max(radius);
+
+    (look_at_center(transform, bounds, distance), scale)
+}
+
+fn padded_radius(bounds: &Bounds, padding: f32) -> f32 {
+    (bounds.radius() * (1.0 + padding.max(0.0))).max(f32::EPSILON)
+}
+
+fn look_at_center(transform: &LookTransform, bounds: &Bounds, distance: f32) -> LookTransform {
+    let direction = (transform.target - transform.eye).normalize_or_zero();
+    let direction = if direction == Vec3::ZERO {
+        -Vec3::Z
+    } else {
+        direction
+    };
+    let target = bounds.center();
+
+    LookTransform::new(target - distance * direction, target, transform.up)
+}
+
+// ████████╗███████╗███████╗████████╗
+// ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝
+//    ██║   █████╗  ███████╗   ██║
+//    ██║   ██╔══╝  ╚════██║   ██║
+//    ██║   ███████╗███████║   ██║
+//    ╚═╝   ╚══════╝╚══════╝   ╚═╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+    use bevy::app::Events;
+    use std::f32::consts::PI;
+
+    fn transform() -> LookTransform {
+        LookTransform::new(Vec3::new(0.0, 0.0, 10.0), Vec3::ZERO, Vec3::Y)
+    }
+
+    #[test]
+    fn test_bounds_from_points() {
+        assert_eq!(Bounds::from_points(Vec::new()), None);
+
+        let bounds = Bounds::from_points(vec![
+            Vec3::new(1.0, 2.0, 3.0),
+            Vec3::new(-1.0, 0.0, 5.0),
+            Vec3::new(0.0, 4.0, 4.0),
+        ])
+        .unwrap();
+        assert_eq!(
+            bounds,
+            Bounds::Aabb {
+                min: Vec3::new(-1.0, 0.0, 3.0),
+                max: Vec3::new(1.0, 4.0, 5.0)
+            }
+        );
+        assert_eq!(bounds.center(), Vec3::new(0.0, 2.0, 4.0));
+        assert_relative_eq!(bounds.radius(), 6.0f32.sqrt());
+    }
+
+    #[test]
+    fn test_frame_perspective_keeps_direction() {
+        let bounds = Bounds::Sphere {
+            center: Vec3::new(5.0, 1.0, -3.0),
+            radius: 1.0,
+        };
+        let framed = frame_perspective(&transform(), &bounds, 0.0, 0.5 * PI, 1.0);
+
+        assert_eq!(framed.target, bounds.center());
+        assert_relative_eq!(framed.look_direction().z, -1.0, epsilon = 1e-6);
+
+        // With a 90 degree field of view, the sphere touches the edges of the view at this distance.
+        assert_relative_eq!(framed.radius(), 2.0f32.sqrt(), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_frame_perspective_fits_narrow_view() {
+        let bounds = Bounds::Sphere {
+            center: Vec3::ZERO,
+            radius: 1.0,
+        };
+        let wide = frame_perspective(&transform(), &bounds, 0.1, 0.5 * PI, 2.0);
+        let narrow = frame_perspective(&transform(), &bounds, 0.1, 0.5 * PI, 0.5);
+
+        // In the narrow view, the horizontal field of view is the limit.
+        assert!(narrow.radius() > wide.radius());
+        let half_horizontal_fov = 0.5f32.atan();
+        assert_relative_eq!(
+            (1.1 / narrow.radius()).asin(),
+            half_horizontal_fov,
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn test_frame_orthographic_scale() {
+        let mut projection = OrthographicProjection::default();
+        projection.update(800.0, 600.0);
+
+        let bounds = Bounds::Sphere {
+            center: Vec3::new(0.0, 0.0, -5.0),
+            radius: 30.0,
+        };
+        let (framed, scale) = frame_orthographic(&transform(), &bounds, 0.0, &projection);
+
+        assert_relative_eq!(scale, 0.1);
+        assert_eq!(framed.target, bounds.center());
+        assert!(framed.radius() >= bounds.radius());
+    }
+
+    #[test]
+    fn test_frame_system_eases_orthographic_scale() {
+        let mut world = World::default();
+        world.insert_resource(Time::default());
+        world.insert_resource(Events::<FrameEvent>::default());
+
+        let mut projection = OrthographicProjection::default();
+        projection.update(800.0, 600.0);
+        let smoothed = world
+            .spawn()
+            .insert(transform())
+            .insert(Smoother::new(0.8))
+            .insert(projection.clone())
+            .id();
+        let unsmoothed = world
+            .spawn()
+            .insert(transform())
+            .insert(projection.clone())
+            .id();
+
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(frame_system.system());
+
+        let bounds = Bounds::Sphere {
+            center: Vec3::new(2.0, 1.0, 0.0),
+            radius: 30.0,
+        };
+        let (framed, scale) = frame_orthographic(&transform(), &bounds, 0.0, &projection);
+        let mut events = world.get_resource_mut::<Events<FrameEvent>>().unwrap();
+        for &camera in [smoothed, unsmoothed].iter() {
+            events.send(FrameEvent {
+                camera,
+                bounds,
+                padding: 0.0,
+            });
+        }
+        stage.run(&mut world);
+
+        // The `Smoother` animates the `LookTransform`, so the scale waits to ease along with it rather than snapping.
+        let transform = world.get::<LookTransform>(smoothed).unwrap();
+        assert_eq!(transform.eye, framed.eye);
+        assert_eq!(transform.target, framed.target);
+        assert_relative_eq!(
+            world.get::<OrthographicProjection>(smoothed).unwrap().scale,
+            projection.scale
+        );
+
+        // Without a `Smoother`, the camera jumps to the framed view.
+        let transform = world.get::<LookTransform>(unsmoothed).unwrap();
+        assert_eq!(transform.eye, framed.eye);
+        assert_eq!(transform.target, framed.target);
+        assert_relative_eq!(
+            world
+                .get::<OrthographicProjection>(unsmoothed)
+                .unwrap()
+                .scale,
+            scale
+        );
+    }
+
+    #[test]
+    fn test_smooth_scale_converges() {
+        let dt = 1.0 / 60.0;
+
+        let mut scale = 1.0;
+        scale = smooth_scale(scale, 4.0, 0.8, dt);
+        assert!(scale > 1.0 && scale < 4.0);
+
+        for _ in 0..600 {
+            scale = smooth_scale(scale, 4.0, 0.8, dt);
+        }
+        assert_eq!(scale, 4.0);
+
+        // No smoothing jumps straight to the desired scale.
+        assert_eq!(smooth_scale(1.0, 4.0, 0.0, dt), 4.0);
+    }
+}
@@ -5,10 +5,18 @@
 //! All controllers are based on a `LookTransform` component, which is just an `eye` point that looks at a `target` point. By
 //! modifying this component, the scene graph `Transform` will automatically be synchronized.
 //!
-//! Any entities with `{Transform, LookTransform, Smoother}` components will automatically have their `Transform` smoothed.
-//! Smoothing will have no effect on the `LookTransform`, only the final `Transform` in the scene graph.
+//! The `up` vector determines which way is up for the camera, so Z-up worlds just use `Vec3::Z`. Tilting it rolls the camera.
+//! The default `PlanePicker` picks on the XZ plane though, so use `PlanePicker::ground(Vec3::Z)` for Z-up ground picking.
 //!
-//! ```rust
+//! Any entities with `{Transform, LookTransform, Smoother}` components will automatically have their `Transform` smoothed.
+//! Smoothing will have no effect on the `LookTransform`, only the final `Transform` in the scene graph. Smoothing is based on
+//! elapsed time, so it looks the same at any frame rate.
+//! With `SmoothingMode::Spherical`, the eye is smoothed along the sphere around the target instead of in a straight
+//! line, which the orbit controller uses by default.
+//! `SmoothingMode::Rotation` slerps the whole orientation instead, so the up vector turns along with the eye, which the
+//! trackball controller uses to tumble smoothly over the top of its target.
+//!
+//! ```rust,no_run
 //! use bevy::prelude::*;
 //! use smooth_bevy_cameras::{LookTransform, LookTransformBundle, LookTransformPlugin, Smoother};
 //!
@@ -27,7 +35,7 @@
 //!
 //!     commands
 //!         .spawn_bundle(LookTransformBundle {
-//!             transform: LookTransform { eye, target },
+//!             transform: LookTransform::new(eye, target, Vec3::Y),
 //!             smoother: Smoother::new(0.9), // Value between 0.0 and 1.0, higher is smoother.
 //!         })
 //!         .insert_bundle(PerspectiveCameraBundle::default());
@@ -49,18 +57,48 @@
 //! use bevy::prelude::*;
 //! use smooth_bevy_cameras::{
 //!     LookAngles,
+//!     LookAnglesError,
 //!     LookTransform
 //! };
 //!
-//! fn look_angles(mut transform: LookTransform, delta: Vec2) {
-//!     let mut angles = LookAngles::from_vector(transform.look_direction());
+//! fn look_angles(mut transform: LookTransform, delta: Vec2) -> Result<(), LookAnglesError> {
+//!     let mut angles = LookAngles::from_vector_with_up(transform.look_direction(), transform.up)?;
 //!     angles.add_pitch(delta.y);
 //!     angles.add_yaw(delta.x);
 //!     transform.target = transform.target + 1.0 * transform.radius() * angles.unit_vector();
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! This is how the built-in controllers implement rotation controls. A degenerate direction, like the one you get when the
+//! `eye` is at the `target`, results in a `LookAnglesError`; the built-in controllers recover from this by keeping the
+//! camera's last orientation.
+//!
+//! To restrict the pitch, or the yaw to an arc around some heading, use `LookAngles::with_limits`. The built-in controllers
+//! expose this as their `angle_limits` field.
+//!
+//! # Framing
+//!
+//! To move any `LookTransform` camera so some bounds fit in its view, like "frame selected" in an editor, send a `FrameEvent`
+//! to the camera. The look direction is kept, and the camera's `Smoother` animates the move:
+//!
+//! ```rust
+//! use bevy::prelude::*;
+//! use smooth_bevy_cameras::{Bounds, FrameEvent};
+//!
+//! fn frame_selected(mut frame_events: EventWriter<FrameEvent>, camera: Entity, center: Vec3, radius: f32) {
+//!     frame_events.send(FrameEvent {
+//!         camera,
+//!         bounds: Bounds::Sphere { center, radius },
+//!         padding: 0.1,
+//!     });
 //! }
 //! ```
 //!
-//! This is how the built-in controllers implement rotation controls.
+//! Perspective cameras move along their look direction, while orthographic cameras change their projection scale, which eases
+//! along with the `Smoother`. The pure functions `frame_perspective` and `frame_orthographic` compute the same results for
+//! your own systems.
 //!
 //! # Built-In Controllers
 //!
@@ -70,19 +108,72 @@
 //!   - WASD: Translate on the XZ plane
 //!   - Shift/Space: Translate along the Y axis
 //!   - Mouse: Rotate camera
+//!   - Gamepad: Left stick translates, right stick rotates, triggers translate along the Y axis
 //! - `OrbitCameraPlugin + OrbitCameraBundle`
-//!   - CTRL + mouse drag: Rotate camera
+//!   - CTRL + mouse drag: Rotate camera, or rotate around the point under the cursor with `pivot_on_cursor`
 //!   - Right mouse drag: Pan camera
-//!   - Mouse wheel: Zoom
+//!   - Mouse wheel: Zoom, or zoom toward the point under the cursor with `zoom_to_cursor`
+//!   - Gamepad: Right stick orbits, left stick pans, triggers zoom
+//!   - Touch: One-finger drag orbits, two-finger drag pans, pinch zooms
+//!   - No input: Turn like a turntable after `auto_rotate_delay` seconds, until the next input
 //! - `UnrealCameraPlugin + UnrealCameraBundle`
 //!   - Left mouse drag: Locomotion
 //!   - Right mouse drag: Rotate camera
 //!   - Left and Right mouse drag: Pan camera
+//!   - Gamepad: Left stick for locomotion, right stick rotates, triggers translate along the Y axis
+//! - `FollowCameraPlugin + FollowCameraBundle`
+//!   - Keeps the target on a `FollowTarget` entity, optionally leading it along its velocity with `look_ahead_time`
+//!   - Right mouse drag: Orbit around the entity
+//!   - Mouse wheel: Zoom
+//!   - Gamepad: Right stick orbits
+//! - `ShoulderCameraPlugin + ShoulderCameraBundle`
+//!   - Looks over the shoulder of a `FollowTarget` entity
+//!   - Mouse: Rotate camera
+//!   - Q: Swap shoulders
+//!   - Right mouse: Aim, which moves closer and narrows the field of view
+//!   - Gamepad: Right stick rotates, left trigger aims
+//! - `RtsCameraPlugin + RtsCameraBundle`
+//!   - WASD/Arrows or cursor at the edge of the window: Pan on the ground
+//!   - Q/E or middle mouse drag: Rotate around the center of the view
+//!   - Mouse wheel: Zoom, which changes the height and pitch of the camera
+//! - `Ortho2dCameraPlugin + Ortho2dCameraBundle`
+//!   - For 2D games and map views with an `OrthographicCameraBundle`
+//!   - Right mouse drag or WASD: Pan
+//!   - Mouse wheel: Zoom toward the cursor by changing the orthographic scale
+//! - `TrackballCameraPlugin + TrackballCameraBundle`
+//!   - For inspecting models: tumbles freely over the top of the target, without a pitch limit
+//!   - Left mouse drag: Turn the virtual sphere under the cursor, or roll when dragging around its edge
+//!   - Q/E: Roll camera
+//!   - Right mouse drag: Pan camera
+//!   - Mouse wheel: Zoom
+//! - `GroupCameraPlugin + GroupCameraBundle`
+//!   - Keeps the entities in `GroupCameraTargets` in view, looking in a fixed direction
+//!
+//! The inputs listed above are only defaults. Each plugin reads its bindings from a serializable resource
+//! (`FpsCameraBindings`, `OrbitCameraBindings`, `UnrealCameraBindings`, `FollowCameraBindings`,
+//! `ShoulderCameraBindings`, `RtsCameraBindings`, `Ortho2dCameraBindings`, `TrackballCameraBindings`) that you can
+//! insert before adding the plugin.
+//!
+//! Each controller's `ControlEvent`s carry the `Entity` of the camera they are meant for, so any number of cameras can be
+//! driven independently. The default input maps send the same user input to every camera whose controller is `enabled`;
+//! disable a controller to stop it from responding, or write your own input map to route input to specific cameras.
+//!
+//! Speeds of held inputs, like keys, sticks and triggers, are per second and scaled by the frame time. Mouse, trackpad and
+//! touch sensitivities are per pixel or line of motion instead, which already adds up the same at any frame rate.
+//!
+//! The `FpsCameraBundle`, `OrbitCameraBundle`, `UnrealCameraBundle` and `TrackballCameraBundle` also accept an
+//! `OrthographicCameraBundle` in place of the `PerspectiveCameraBundle`. Since moving the eye doesn't change the size of an
+//! orthographic view, the orbit and trackball controllers zoom by scaling the `OrthographicProjection` instead, between their
+//! `min_orthographic_scale` and `max_orthographic_scale`.
 
 pub mod controllers;
 
+mod framing;
 mod look_angles;
 mod look_transform;
+mod ray;
 
+pub use framing::*;
 pub use look_angles::*;
 pub use look_transform::*;
+pub use ray::*;
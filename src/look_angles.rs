@@ -1,35 +1,166 @@
 use approx::relative_eq;
 use bevy::math::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
 const PI: f32 = std::f32::consts::PI;
 
-/// A (yaw, pitch) pair representing a direction.
-#[derive(Clone, Copy, Debug, Default)]
+/// A (yaw, pitch) pair representing a direction. Yaw rotates about the `up` axis, and pitch rotates towards it.
+///
+/// With the default `up` of `Vec3::Y`, a yaw of zero faces `Vec3::Z`. For any other `up`, the zero yaw direction is rotated
+/// along with the up axis (by the shortest arc from `Vec3::Y` to `up`).
+#[derive(Clone, Copy, Debug)]
 pub struct LookAngles {
     // The fields are protected to keep them in an allowable range for the camera transform.
     yaw: f32,
     pitch: f32,
+    up: Vec3,
+    limits: AngleLimits,
 }
 
+impl Default for LookAngles {
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.0,
+            up: Vec3::Y,
+            limits: AngleLimits::default(),
+        }
+    }
+}
+
+/// The range of angles (in radians) that `LookAngles` are kept in. By default, only the pitch is limited, to keep from looking
+/// straight along the up axis.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AngleLimits {
+    pub min_pitch: f32,
+    pub max_pitch: f32,
+    /// If set, the yaw is kept within this arc.
+    pub yaw: Option<YawRange>,
+}
+
+impl Default for AngleLimits {
+    fn default() -> Self {
+        Self {
+            min_pitch: -PI / 2.0,
+            max_pitch: PI / 2.0,
+            yaw: None,
+        }
+    }
+}
+
+/// An arc of yaw angles, extending `half_width` radians to either side of `heading`. The arc may contain the seam at `±PI`.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct YawRange {
+    pub heading: f32,
+    pub half_width: f32,
+}
+
+impl YawRange {
+    /// Returns the angle in this range that is closest to `yaw`.
+    pub fn clamp(&self, yaw: f32) -> f32 {
+        let offset = wrap_angle(yaw - self.heading);
+        if offset.abs() <= self.half_width {
+            return yaw;
+        }
+
+        // Snap to whichever end of the arc is closer, going around the circle.
+        let past_max = wrap_angle(offset - self.half_width).abs();
+        let past_min = wrap_angle(offset + self.half_width).abs();
+        if past_max <= past_min {
+            self.heading + self.half_width
+        } else {
+            self.heading - self.half_width
+        }
+    }
+}
+
+/// The reasons a direction can't be represented with `LookAngles`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LookAnglesError {
+    /// The direction has zero length or isn't finite, e.g. because the eye and target of a `LookTransform` are equal.
+    ZeroDirection,
+    /// The up vector has zero length or isn't finite.
+    ZeroUp,
+    /// The direction is parallel to the up vector, so the yaw is undefined.
+    ParallelToUp,
+}
+
+impl fmt::Display for LookAnglesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroDirection => write!(f, "look direction is zero or not finite"),
+            Self::ZeroUp => write!(f, "up vector is zero or not finite"),
+            Self::ParallelToUp => write!(f, "look direction is parallel to the up vector"),
+        }
+    }
+}
+
+impl std::error::Error for LookAnglesError {}
+
 impl LookAngles {
-    pub fn from_vector(v: Vec3) -> Self {
-        let mut p = Self::default();
-        p.set_direction(v);
+    pub fn from_vector(v: Vec3) -> Result<Self, LookAnglesError> {
+        Self::from_vector_with_up(v, Vec3::Y)
+    }
+
+    pub fn from_vector_with_up(v: Vec3, up: Vec3) -> Result<Self, LookAnglesError> {
+        if !is_valid_direction(up) {
+            return Err(LookAnglesError::ZeroUp);
+        }
+
+        let mut p = Self {
+            up: up.normalize(),
+            ..Default::default()
+        };
+        p.set_direction(v)?;
+
+        Ok(p)
+    }
+
+    pub fn up(&self) -> Vec3 {
+        self.up
+    }
+
+    /// Applies the `limits` to the current and all future angles.
+    pub fn with_limits(mut self, limits: AngleLimits) -> Self {
+        self.set_limits(limits);
+
+        self
+    }
 
-        p
+    pub fn set_limits(&mut self, limits: AngleLimits) {
+        self.limits = limits;
+        self.set_yaw(self.yaw);
+        self.set_pitch(self.pitch);
+    }
+
+    pub fn limits(&self) -> AngleLimits {
+        self.limits
     }
 
     pub fn unit_vector(self) -> Vec3 {
-        unit_vector_from_yaw_and_pitch(self.yaw, self.pitch)
+        self.frame() * unit_vector_from_yaw_and_pitch(self.yaw, self.pitch)
+    }
+
+    /// The rotation from the Y-up basis to the basis whose Y axis is `up` and whose Z axis is the horizontal look direction.
+    pub fn yaw_rotation(self) -> Quat {
+        self.frame() * Quat::from_axis_angle(Vec3::Y, self.yaw)
     }
 
-    pub fn set_direction(&mut self, v: Vec3) {
-        let (yaw, pitch) = yaw_and_pitch_from_vector(v);
+    /// Points the angles in the direction of `v`. A `v` parallel to the up axis is nudged slightly away from it.
+    pub fn set_direction(&mut self, v: Vec3) -> Result<(), LookAnglesError> {
+        let (yaw, pitch) = yaw_and_pitch_from_vector(self.frame().inverse() * v)?;
         self.set_yaw(yaw);
         self.set_pitch(pitch);
+
+        Ok(())
     }
 
     pub fn set_yaw(&mut self, yaw: f32) {
+        let yaw = match self.limits.yaw {
+            Some(range) => range.clamp(yaw),
+            None => yaw,
+        };
         self.yaw = yaw % (2.0 * PI);
     }
 
@@ -38,13 +169,23 @@ impl LookAngles {
     }
 
     pub fn add_yaw(&mut self, delta: f32) {
-        self.set_yaw(self.get_yaw() + delta);
+        if let Some(range) = self.limits.yaw {
+            // Clamp the offset from the heading before wrapping it, so we stop at the end of the arc instead of wrapping around
+            // to the other end.
+            let offset = wrap_angle(self.yaw - range.heading) + delta;
+            let offset = offset.min(range.half_width).max(-range.half_width);
+            self.yaw = (range.heading + offset) % (2.0 * PI);
+        } else {
+            self.set_yaw(self.get_yaw() + delta);
+        }
     }
 
     pub fn set_pitch(&mut self, pitch: f32) {
         // Things can get weird if we are parallel to the UP vector.
         let up_eps = 0.01;
-        self.pitch = pitch.min(PI / 2.0 - up_eps).max(-PI / 2.0 + up_eps);
+        let max_pitch = self.limits.max_pitch.min(PI / 2.0 - up_eps);
+        let min_pitch = self.limits.min_pitch.max(-PI / 2.0 + up_eps);
+        self.pitch = pitch.min(max_pitch).max(min_pitch);
     }
 
     pub fn get_pitch(&self) -> f32 {
@@ -55,21 +196,26 @@ impl LookAngles {
         self.set_pitch(self.get_pitch() + delta);
     }
 
-    pub fn assert_not_looking_up(&self) {
-        let is_looking_up = relative_eq!(self.unit_vector().dot(Vec3::Y).abs(), 1.0);
+    pub fn check_not_looking_up(&self) -> Result<(), LookAnglesError> {
+        if relative_eq!(self.unit_vector().dot(self.up).abs(), 1.0) {
+            return Err(LookAnglesError::ParallelToUp);
+        }
 
-        assert!(
-            !is_looking_up,
-            "Your camera transform is fucked up. Your look direction {} is probably bad.",
-            self.unit_vector(),
-        );
+        Ok(())
+    }
+
+    /// Rotates the Y-up basis that the angles are computed in onto `up`.
+    fn frame(self) -> Quat {
+        Quat::from_rotation_arc(Vec3::Y, self.up)
     }
 }
 
 /// Returns pitch and yaw angles that rotates z unit vector to v. The yaw is applied first to z about the y axis to get z'. Then
 /// the pitch is applied about some axis orthogonal to z' in the XZ plane to get v.
-fn yaw_and_pitch_from_vector(v: Vec3) -> (f32, f32) {
-    debug_assert_ne!(v, Vec3::ZERO);
+fn yaw_and_pitch_from_vector(v: Vec3) -> Result<(f32, f32), LookAnglesError> {
+    if !is_valid_direction(v) {
+        return Err(LookAnglesError::ZeroDirection);
+    }
 
     let y = Vec3::Y;
     let z = Vec3::Z;
@@ -78,9 +224,9 @@ fn yaw_and_pitch_from_vector(v: Vec3) -> (f32, f32) {
 
     if v_xz == Vec3::ZERO {
         if v.dot(y) > 0.0 {
-            return (0.0, PI / 2.0);
+            return Ok((0.0, PI / 2.0));
         } else {
-            return (0.0, -PI / 2.0);
+            return Ok((0.0, -PI / 2.0));
         }
     }
 
@@ -94,7 +240,21 @@ fn yaw_and_pitch_from_vector(v: Vec3) -> (f32, f32) {
         pitch *= -1.0;
     }
 
-    (yaw, pitch)
+    Ok((yaw, pitch))
+}
+
+/// Wraps an angle into `[-PI, PI]`.
+fn wrap_angle(angle: f32) -> f32 {
+    let wrapped = (angle + PI).rem_euclid(2.0 * PI) - PI;
+    if wrapped == -PI && angle > 0.0 {
+        PI
+    } else {
+        wrapped
+    }
+}
+
+fn is_valid_direction(v: Vec3) -> bool {
+    v.is_finite() && v != Vec3::ZERO
 }
 
 fn unit_vector_from_yaw_and_pitch(yaw: f32, pitch: f32) -> Vec3 {
@@ -122,7 +282,7 @@ mod tests {
     #[test]
     fn test_yaw_and_pitch_identity() {
         let v = Vec3::new(0.0, 0.0, 1.0);
-        let (yaw, pitch) = yaw_and_pitch_from_vector(v);
+        let (yaw, pitch) = yaw_and_pitch_from_vector(v).unwrap();
 
         assert_relative_eq!(yaw, 0.0);
         assert_relative_eq!(pitch, 0.0);
@@ -130,35 +290,218 @@ mod tests {
 
     #[test]
     fn test_yaw_only() {
-        let (yaw, pitch) = yaw_and_pitch_from_vector(Vec3::new(1.0, 0.0, 0.0));
+        let (yaw, pitch) = yaw_and_pitch_from_vector(Vec3::new(1.0, 0.0, 0.0)).unwrap();
         assert_relative_eq!(yaw, PI / 2.0);
         assert_relative_eq!(pitch, 0.0);
 
-        let (yaw, pitch) = yaw_and_pitch_from_vector(Vec3::new(-1.0, 0.0, 0.0));
+        let (yaw, pitch) = yaw_and_pitch_from_vector(Vec3::new(-1.0, 0.0, 0.0)).unwrap();
         assert_relative_eq!(yaw, -PI / 2.0);
         assert_relative_eq!(pitch, 0.0);
     }
 
     #[test]
     fn test_pitch_only() {
-        let (yaw, pitch) = yaw_and_pitch_from_vector(Vec3::new(0.0, 1.0, 0.0));
+        let (yaw, pitch) = yaw_and_pitch_from_vector(Vec3::new(0.0, 1.0, 0.0)).unwrap();
         assert_relative_eq!(yaw, 0.0);
         assert_relative_eq!(pitch, PI / 2.0);
 
-        let (yaw, pitch) = yaw_and_pitch_from_vector(Vec3::new(0.0, -1.0, 0.0));
+        let (yaw, pitch) = yaw_and_pitch_from_vector(Vec3::new(0.0, -1.0, 0.0)).unwrap();
         assert_relative_eq!(yaw, 0.0);
         assert_relative_eq!(pitch, -PI / 2.0);
     }
 
     #[test]
     fn test_yaw_and_pitch() {
-        let (yaw, pitch) = yaw_and_pitch_from_vector(Vec3::new(0.5f32.sqrt(), 1.0, 0.5f32.sqrt()));
+        let (yaw, pitch) =
+            yaw_and_pitch_from_vector(Vec3::new(0.5f32.sqrt(), 1.0, 0.5f32.sqrt())).unwrap();
         assert_relative_eq!(yaw, PI / 4.0, epsilon = 1e-6f32);
         assert_relative_eq!(pitch, PI / 4.0);
 
         let (yaw, pitch) =
-            yaw_and_pitch_from_vector(Vec3::new(-0.5f32.sqrt(), -1.0, 0.5f32.sqrt()));
+            yaw_and_pitch_from_vector(Vec3::new(-0.5f32.sqrt(), -1.0, 0.5f32.sqrt())).unwrap();
         assert_relative_eq!(yaw, -PI / 4.0, epsilon = 1e-6f32);
         assert_relative_eq!(pitch, -PI / 4.0);
     }
+
+    // Z-up: the shortest rotation from Y to Z takes the zero yaw direction from Z to -Y.
+
+    #[test]
+    fn test_z_up_identity() {
+        let angles = LookAngles::from_vector_with_up(Vec3::new(0.0, -1.0, 0.0), Vec3::Z).unwrap();
+        assert_relative_eq!(angles.get_yaw(), 0.0);
+        assert_relative_eq!(angles.get_pitch(), 0.0);
+    }
+
+    #[test]
+    fn test_z_up_yaw_only() {
+        let angles = LookAngles::from_vector_with_up(Vec3::new(1.0, 0.0, 0.0), Vec3::Z).unwrap();
+        assert_relative_eq!(angles.get_yaw(), PI / 2.0);
+        assert_relative_eq!(angles.get_pitch(), 0.0);
+
+        let angles = LookAngles::from_vector_with_up(Vec3::new(-1.0, 0.0, 0.0), Vec3::Z).unwrap();
+        assert_relative_eq!(angles.get_yaw(), -PI / 2.0);
+        assert_relative_eq!(angles.get_pitch(), 0.0);
+    }
+
+    #[test]
+    fn test_z_up_pitch_only() {
+        // Pitch is clamped just short of the up axis.
+        let angles = LookAngles::from_vector_with_up(Vec3::new(0.0, 0.0, 1.0), Vec3::Z).unwrap();
+        assert_relative_eq!(angles.get_yaw(), 0.0);
+        assert_relative_eq!(angles.get_pitch(), PI / 2.0, epsilon = 0.011);
+
+        let angles = LookAngles::from_vector_with_up(Vec3::new(0.0, 0.0, -1.0), Vec3::Z).unwrap();
+        assert_relative_eq!(angles.get_yaw(), 0.0);
+        assert_relative_eq!(angles.get_pitch(), -PI / 2.0, epsilon = 0.011);
+    }
+
+    #[test]
+    fn test_z_up_yaw_and_pitch() {
+        let v = Vec3::new(0.5f32.sqrt(), -0.5f32.sqrt(), 1.0);
+        let angles = LookAngles::from_vector_with_up(v, Vec3::Z).unwrap();
+        assert_relative_eq!(angles.get_yaw(), PI / 4.0, epsilon = 1e-6f32);
+        assert_relative_eq!(angles.get_pitch(), PI / 4.0, epsilon = 1e-6f32);
+
+        let unit = angles.unit_vector();
+        let expected = v.normalize();
+        assert_relative_eq!(unit.x, expected.x, epsilon = 1e-6f32);
+        assert_relative_eq!(unit.y, expected.y, epsilon = 1e-6f32);
+        assert_relative_eq!(unit.z, expected.z, epsilon = 1e-6f32);
+    }
+
+    #[test]
+    fn test_pitch_limits() {
+        let limits = AngleLimits {
+            min_pitch: -PI / 6.0,
+            max_pitch: PI / 3.0,
+            yaw: None,
+        };
+        let mut angles = LookAngles::default().with_limits(limits);
+
+        angles.add_pitch(1.5);
+        assert_relative_eq!(angles.get_pitch(), PI / 3.0);
+        angles.add_pitch(-3.0);
+        assert_relative_eq!(angles.get_pitch(), -PI / 6.0);
+
+        // Directions outside of the limits are clamped too.
+        angles.set_direction(Vec3::new(0.0, -1.0, 1.0)).unwrap();
+        assert_relative_eq!(angles.get_pitch(), -PI / 6.0);
+    }
+
+    #[test]
+    fn test_yaw_limits() {
+        let limits = AngleLimits {
+            yaw: Some(YawRange {
+                heading: PI / 2.0,
+                half_width: PI / 4.0,
+            }),
+            ..Default::default()
+        };
+        let mut angles = LookAngles::from_vector(Vec3::X)
+            .unwrap()
+            .with_limits(limits);
+        assert_relative_eq!(angles.get_yaw(), PI / 2.0);
+
+        angles.add_yaw(1.0);
+        assert_relative_eq!(angles.get_yaw(), 3.0 * PI / 4.0);
+        angles.add_yaw(-2.0);
+        assert_relative_eq!(angles.get_yaw(), PI / 4.0);
+
+        // Snaps to the closest end of the arc.
+        angles.set_yaw(-PI / 4.0);
+        assert_relative_eq!(angles.get_yaw(), PI / 4.0);
+        angles.set_yaw(-3.0 * PI / 4.0);
+        assert_relative_eq!(angles.get_yaw(), 3.0 * PI / 4.0);
+    }
+
+    #[test]
+    fn test_yaw_limits_across_seam() {
+        // A range centered behind the default heading, which contains the seam at +-PI.
+        let limits = AngleLimits {
+            yaw: Some(YawRange {
+                heading: PI,
+                half_width: PI / 4.0,
+            }),
+            ..Default::default()
+        };
+        let mut angles = LookAngles::from_vector(-Vec3::Z)
+            .unwrap()
+            .with_limits(limits);
+
+        angles.add_yaw(0.5);
+        let yaw = angles.get_yaw();
+        assert_relative_eq!(wrap_angle(yaw - PI), 0.5, epsilon = 1e-5);
+
+        angles.add_yaw(-1.0);
+        assert_relative_eq!(wrap_angle(angles.get_yaw() - PI), -0.5, epsilon = 1e-5);
+
+        angles.add_yaw(-1.0);
+        assert_relative_eq!(wrap_angle(angles.get_yaw() - PI), -PI / 4.0, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_yaw_limits_dont_wrap_through_gap() {
+        let limits = AngleLimits {
+            yaw: Some(YawRange {
+                heading: 0.0,
+                half_width: 0.9 * PI,
+            }),
+            ..Default::default()
+        };
+        let mut angles = LookAngles::default().with_limits(limits);
+
+        angles.add_yaw(0.85 * PI);
+        angles.add_yaw(0.3 * PI);
+        assert_relative_eq!(angles.get_yaw(), 0.9 * PI, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_zero_direction_is_an_error() {
+        assert_eq!(
+            LookAngles::from_vector(Vec3::ZERO).unwrap_err(),
+            LookAnglesError::ZeroDirection
+        );
+
+        // This is what `LookTransform::look_direction` returns when the eye is at the target.
+        let eye = Vec3::new(1.0, 2.0, 3.0);
+        let look_direction = (eye - eye).normalize();
+        assert_eq!(
+            LookAngles::from_vector(look_direction).unwrap_err(),
+            LookAnglesError::ZeroDirection
+        );
+    }
+
+    #[test]
+    fn test_zero_up_is_an_error() {
+        assert_eq!(
+            LookAngles::from_vector_with_up(Vec3::Z, Vec3::ZERO).unwrap_err(),
+            LookAnglesError::ZeroUp
+        );
+    }
+
+    #[test]
+    fn test_vertical_direction_is_nudged() {
+        for v in [Vec3::Y, -Vec3::Y].iter() {
+            let angles = LookAngles::from_vector(*v).unwrap();
+            assert!(angles.check_not_looking_up().is_ok());
+            assert!(angles.unit_vector().dot(*v) < 1.0);
+        }
+    }
+
+    #[test]
+    fn test_z_up_yaw_rotation() {
+        let mut angles =
+            LookAngles::from_vector_with_up(Vec3::new(0.0, -1.0, 0.0), Vec3::Z).unwrap();
+        angles.add_yaw(PI / 2.0);
+        let rotation = angles.yaw_rotation();
+
+        let up = rotation * Vec3::Y;
+        assert_relative_eq!(up.z, 1.0, epsilon = 1e-6f32);
+
+        let forward = rotation * Vec3::Z;
+        let expected = angles.unit_vector();
+        assert_relative_eq!(forward.x, expected.x, epsilon = 1e-6f32);
+        assert_relative_eq!(forward.y, expected.y, epsilon = 1e-6f32);
+        assert_relative_eq!(forward.z, expected.z, epsilon = 1e-6f32);
+    }
 }
@@ -1,39 +1,61 @@
+use crate::{frame_system, FrameEvent};
+
 use bevy::{
-    app::prelude::*,
-    ecs::{bundle::Bundle, prelude::*},
-    math::prelude::*,
+    app::prelude::*, core::Time, ecs::prelude::*, math::prelude::*,
     transform::components::Transform,
 };
+use serde::{Deserialize, Serialize};
 
 pub struct LookTransformPlugin;
 
 impl Plugin for LookTransformPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_system(look_transform_system.system());
+        app.add_system(look_transform_system.system())
+            .add_system(frame_system.system())
+            .add_event::<FrameEvent>();
     }
 }
 
-#[derive(Bundle)]
-pub struct LookTransformBundle {
-    pub transform: LookTransform,
-    pub smoother: Smoother,
+pub use bundle::LookTransformBundle;
+
+// The `Bundle` derive forgets its fields after moving them into storage, which clippy flags for the `Smoother`. The derived
+// impl doesn't inherit attributes from the struct, so the allow has to be on an enclosing module.
+#[allow(clippy::forget_non_drop)]
+mod bundle {
+    use super::{LookTransform, Smoother};
+
+    use bevy::ecs::bundle::Bundle;
+
+    #[derive(Bundle)]
+    pub struct LookTransformBundle {
+        pub transform: LookTransform,
+        pub smoother: Smoother,
+    }
 }
 
 /// An eye and the target it's looking at. As a component, this can be modified in place of bevy's `Transform`, and the two will
 /// stay in sync.
+///
+/// The `up` vector orients the camera around its look direction. It is usually the world's up axis (e.g. `Vec3::Z` in a Z-up
+/// scene), but tilting it rolls the camera. It must not be parallel to the look direction.
 #[derive(Clone, Copy, Debug)]
 pub struct LookTransform {
     pub eye: Vec3,
     pub target: Vec3,
+    pub up: Vec3,
 }
 
 impl From<LookTransform> for Transform {
     fn from(t: LookTransform) -> Self {
-        eye_look_at_target_transform(t.eye, t.target)
+        eye_look_at_target_transform(t.eye, t.target, t.up)
     }
 }
 
 impl LookTransform {
+    pub fn new(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        Self { eye, target, up }
+    }
+
     pub fn radius(&self) -> f32 {
         (self.target - self.eye).length()
     }
@@ -41,61 +63,629 @@ impl LookTransform {
     pub fn look_direction(&self) -> Vec3 {
         (self.target - self.eye).normalize()
     }
+
+    /// Returns `false` if the orientation is undefined, i.e. the eye is at the target or the up vector is zero or parallel to
+    /// the look direction.
+    pub fn has_orientation(&self) -> bool {
+        let look = self.target - self.eye;
+
+        look.is_finite()
+            && self.up.is_finite()
+            && look.cross(self.up).length_squared() > f32::EPSILON * look.length_squared()
+    }
+
+    /// The rotation of a camera `Transform` at the eye, i.e. the one that turns `-Z` into the look direction and `Y` towards the
+    /// up vector. Returns `None` if the orientation is undefined.
+    pub fn rotation(&self) -> Option<Quat> {
+        if !self.has_orientation() {
+            return None;
+        }
+
+        Some(
+            Transform::identity()
+                .looking_at(self.target - self.eye, self.up)
+                .rotation,
+        )
+    }
 }
 
-fn eye_look_at_target_transform(eye: Vec3, target: Vec3) -> Transform {
+fn eye_look_at_target_transform(eye: Vec3, target: Vec3, up: Vec3) -> Transform {
     // If eye and target are very close, we avoid imprecision issues by keeping the look vector a unit vector.
     let look_vector = (target - eye).normalize();
     let look_at = eye + look_vector;
 
-    Transform::from_translation(eye).looking_at(look_at, Vec3::Y)
+    Transform::from_translation(eye).looking_at(look_at, up)
 }
 
 /// Preforms exponential smoothing on a `LookTransform`. Set the `lag_weight` between `0.0` and `1.0`, where higher is smoother.
+///
+/// The `lag_weight` is the fraction of the remaining distance that is kept after one reference frame of `1/60` seconds. It gets
+/// rescaled for the actual frame time, so the smoothed motion is the same regardless of frame rate. If you prefer to think in
+/// seconds, see `Smoother::from_half_life`.
+///
+/// The `eye` and `target` share the `lag_weight`, but either can override it, e.g. to keep the target tight while the eye trails
+/// behind.
+///
+/// To jump to a new transform without smoothing, e.g. when respawning, call `Smoother::reset`. A `Smoother` can also snap
+/// automatically when the transform jumps further than a distance or angle threshold.
 pub struct Smoother {
     lag_weight: f32,
+    eye_lag_weight: Option<f32>,
+    target_lag_weight: Option<f32>,
+    mode: SmoothingMode,
+    snap_distance: Option<f32>,
+    snap_angle: Option<f32>,
     lerp_tfm: Option<LookTransform>,
 }
 
+/// How a `Smoother` interpolates from the old smoothed transform to the new one.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum SmoothingMode {
+    /// Interpolates `eye` and `target` as independent points.
+    #[default]
+    Linear,
+    /// Interpolates `target` linearly, but the `eye` in target-relative spherical coordinates: its direction from the target is
+    /// rotated along the great circle, and its distance is interpolated linearly. This keeps an orbiting eye from cutting
+    /// through the target.
+    Spherical,
+    /// Interpolates `target` linearly, but the orientation of the camera with a quaternion slerp, while the `eye` stays at the
+    /// interpolated distance behind the target. The look direction and up vector rotate together, so a camera that tumbles
+    /// over the top of its target doesn't twist along the way.
+    Rotation,
+}
+
+/// The frame duration that a `Smoother`'s lag weights are defined for.
+pub const SMOOTHER_REFERENCE_FRAME_SECONDS: f32 = 1.0 / 60.0;
+
 impl Smoother {
     pub fn new(lag_weight: f32) -> Self {
+        Self::new_separate(lag_weight, None, None)
+    }
+
+    /// Creates a `Smoother` whose `eye` and `target` share the `lag_weight`, unless they have their own.
+    pub fn new_separate(
+        lag_weight: f32,
+        eye_lag_weight: Option<f32>,
+        target_lag_weight: Option<f32>,
+    ) -> Self {
         Self {
             lag_weight,
+            eye_lag_weight,
+            target_lag_weight,
+            mode: SmoothingMode::default(),
+            snap_distance: None,
+            snap_angle: None,
             lerp_tfm: None,
         }
     }
 
+    /// Creates a `Smoother` that closes half of the remaining distance to the target every `half_life` seconds.
+    pub fn from_half_life(half_life: f32) -> Self {
+        Self::new(lag_weight_from_half_life(half_life))
+    }
+
+    /// Sets the lag weight shared by the `eye` and `target`. Their own lag weights still take precedence.
     pub fn set_lag_weight(&mut self, lag_weight: f32) {
         self.lag_weight = lag_weight;
     }
 
-    pub fn smooth_transform(&mut self, new_tfm: &LookTransform) -> LookTransform {
-        debug_assert!(0.0 <= self.lag_weight);
-        debug_assert!(self.lag_weight < 1.0);
+    pub fn lag_weight(&self) -> f32 {
+        self.lag_weight
+    }
+
+    /// Overrides the lag weight of the `eye`, or shares the `lag_weight` again with `None`.
+    pub fn set_eye_lag_weight(&mut self, lag_weight: Option<f32>) {
+        self.eye_lag_weight = lag_weight;
+    }
+
+    /// The lag weight that is applied to the `eye`.
+    pub fn eye_lag_weight(&self) -> f32 {
+        self.eye_lag_weight.unwrap_or(self.lag_weight)
+    }
+
+    /// Overrides the lag weight of the `target`, or shares the `lag_weight` again with `None`.
+    pub fn set_target_lag_weight(&mut self, lag_weight: Option<f32>) {
+        self.target_lag_weight = lag_weight;
+    }
+
+    /// The lag weight that is applied to the `target`.
+    pub fn target_lag_weight(&self) -> f32 {
+        self.target_lag_weight.unwrap_or(self.lag_weight)
+    }
+
+    pub fn set_mode(&mut self, mode: SmoothingMode) {
+        self.mode = mode;
+    }
+
+    pub fn mode(&self) -> SmoothingMode {
+        self.mode
+    }
+
+    /// Forgets the smoothed transform, so the next update jumps straight to the current `LookTransform`.
+    pub fn reset(&mut self) {
+        self.lerp_tfm = None;
+    }
+
+    /// Skip smoothing whenever the `eye` or `target` jumps further than `distance` from its smoothed position.
+    pub fn set_snap_distance(&mut self, distance: Option<f32>) {
+        self.snap_distance = distance;
+    }
+
+    pub fn snap_distance(&self) -> Option<f32> {
+        self.snap_distance
+    }
+
+    /// Skip smoothing whenever the look direction changes by more than `angle` radians from the smoothed look direction.
+    pub fn set_snap_angle(&mut self, angle: Option<f32>) {
+        self.snap_angle = angle;
+    }
+
+    pub fn snap_angle(&self) -> Option<f32> {
+        self.snap_angle
+    }
 
-        let old_lerp_tfm = self.lerp_tfm.unwrap_or_else(|| *new_tfm);
+    /// Sets the half-life shared by the `eye` and `target`. Their own half-lives still take precedence.
+    pub fn set_half_life(&mut self, half_life: f32) {
+        self.lag_weight = lag_weight_from_half_life(half_life);
+    }
+
+    /// The number of seconds it takes to close half of the remaining distance to the goal, unless the `eye` or `target` has its
+    /// own half-life.
+    pub fn half_life(&self) -> f32 {
+        half_life_from_lag_weight(self.lag_weight)
+    }
 
-        let lead_weight = 1.0 - self.lag_weight;
-        let lerp_tfm = LookTransform {
-            eye: old_lerp_tfm.eye * self.lag_weight + new_tfm.eye * lead_weight,
-            target: old_lerp_tfm.target * self.lag_weight + new_tfm.target * lead_weight,
+    /// Overrides the half-life of the `eye`, or shares the `half_life` again with `None`.
+    pub fn set_eye_half_life(&mut self, half_life: Option<f32>) {
+        self.eye_lag_weight = half_life.map(lag_weight_from_half_life);
+    }
+
+    /// The number of seconds it takes the `eye` to close half of the remaining distance to its goal.
+    pub fn eye_half_life(&self) -> f32 {
+        half_life_from_lag_weight(self.eye_lag_weight())
+    }
+
+    /// Overrides the half-life of the `target`, or shares the `half_life` again with `None`.
+    pub fn set_target_half_life(&mut self, half_life: Option<f32>) {
+        self.target_lag_weight = half_life.map(lag_weight_from_half_life);
+    }
+
+    /// The number of seconds it takes the `target` to close half of the remaining distance to its goal.
+    pub fn target_half_life(&self) -> f32 {
+        half_life_from_lag_weight(self.target_lag_weight())
+    }
+
+    /// Moves the smoothed transform towards `new_tfm`, where `dt` is the number of seconds elapsed since the last call.
+    pub fn smooth_transform(&mut self, new_tfm: &LookTransform, dt: f32) -> LookTransform {
+        let eye_lag_weight = self.eye_lag_weight();
+        let target_lag_weight = self.target_lag_weight();
+        debug_assert!(0.0 <= eye_lag_weight);
+        debug_assert!(eye_lag_weight < 1.0);
+        debug_assert!(0.0 <= target_lag_weight);
+        debug_assert!(target_lag_weight < 1.0);
+        debug_assert!(0.0 <= dt);
+
+        let old_lerp_tfm = match self.lerp_tfm {
+            Some(old_lerp_tfm) if !self.should_snap(&old_lerp_tfm, new_tfm) => old_lerp_tfm,
+            _ => *new_tfm,
+        };
+
+        let frames = dt / SMOOTHER_REFERENCE_FRAME_SECONDS;
+        let eye_lead_weight = 1.0 - eye_lag_weight.powf(frames);
+        let target_lead_weight = 1.0 - target_lag_weight.powf(frames);
+
+        let target = old_lerp_tfm.target.lerp(new_tfm.target, target_lead_weight);
+        let lerp_tfm = match (self.mode, old_lerp_tfm.rotation(), new_tfm.rotation()) {
+            (SmoothingMode::Rotation, Some(old_rotation), Some(new_rotation)) => {
+                let rotation = slerp_rotation(old_rotation, new_rotation, eye_lead_weight);
+                let radius = old_lerp_tfm.radius()
+                    + (new_tfm.radius() - old_lerp_tfm.radius()) * eye_lead_weight;
+                LookTransform {
+                    eye: target + radius * (rotation * Vec3::Z),
+                    target,
+                    up: rotation * Vec3::Y,
+                }
+            }
+            _ => {
+                let eye = match self.mode {
+                    SmoothingMode::Linear => old_lerp_tfm.eye.lerp(new_tfm.eye, eye_lead_weight),
+                    // Without an orientation to slerp, the eye still shouldn't cut through the target.
+                    SmoothingMode::Spherical | SmoothingMode::Rotation => {
+                        target
+                            + slerp_offset(
+                                old_lerp_tfm.eye - old_lerp_tfm.target,
+                                new_tfm.eye - new_tfm.target,
+                                eye_lead_weight,
+                                new_tfm.up,
+                            )
+                    }
+                };
+                // The up vector rolls with the eye. If it flips, roll around the look direction.
+                let up = slerp_offset(
+                    old_lerp_tfm.up,
+                    new_tfm.up,
+                    eye_lead_weight,
+                    new_tfm.target - new_tfm.eye,
+                );
+                LookTransform { eye, target, up }
+            }
         };
 
         self.lerp_tfm = Some(lerp_tfm);
 
         lerp_tfm
     }
+
+    fn should_snap(&self, old_tfm: &LookTransform, new_tfm: &LookTransform) -> bool {
+        if let Some(distance) = self.snap_distance {
+            if old_tfm.eye.distance(new_tfm.eye) > distance
+                || old_tfm.target.distance(new_tfm.target) > distance
+            {
+                return true;
+            }
+        }
+
+        if let Some(angle) = self.snap_angle {
+            let old_dir = (old_tfm.target - old_tfm.eye).normalize_or_zero();
+            let new_dir = (new_tfm.target - new_tfm.eye).normalize_or_zero();
+            if old_dir != Vec3::ZERO
+                && new_dir != Vec3::ZERO
+                && old_dir.angle_between(new_dir) > angle
+            {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+/// Interpolates between two offsets by rotating the direction and interpolating the length. When the offsets point in opposite
+/// directions, they are rotated around `axis_hint` (or as close to it as possible).
+fn slerp_offset(from: Vec3, to: Vec3, t: f32, axis_hint: Vec3) -> Vec3 {
+    let from_length = from.length();
+    let to_length = to.length();
+    if from_length == 0.0 || to_length == 0.0 {
+        return from.lerp(to, t);
+    }
+
+    let from_dir = from / from_length;
+    let to_dir = to / to_length;
+    let rotation = if from_dir.dot(to_dir) < -0.9999 {
+        let axis = (axis_hint - axis_hint.dot(from_dir) * from_dir).normalize_or_zero();
+        let axis = if axis == Vec3::ZERO {
+            from_dir.any_orthonormal_vector()
+        } else {
+            axis
+        };
+        Quat::from_axis_angle(axis, std::f32::consts::PI)
+    } else {
+        Quat::from_rotation_arc(from_dir, to_dir)
+    };
+
+    let length = from_length + (to_length - from_length) * t;
+
+    // Normalize to avoid drift in the radius, since slerp falls back to lerp for small angles.
+    length * (Quat::IDENTITY.slerp(rotation, t) * from_dir).normalize()
+}
+
+/// Interpolates between two rotations along the shorter of the two arcs between them.
+fn slerp_rotation(from: Quat, to: Quat, t: f32) -> Quat {
+    let to = if from.dot(to) < 0.0 { -to } else { to };
+
+    from.slerp(to, t).normalize()
+}
+
+fn lag_weight_from_half_life(half_life: f32) -> f32 {
+    if half_life <= 0.0 {
+        return 0.0;
+    }
+
+    0.5f32.powf(SMOOTHER_REFERENCE_FRAME_SECONDS / half_life)
+}
+
+fn half_life_from_lag_weight(lag_weight: f32) -> f32 {
+    if lag_weight <= 0.0 {
+        return 0.0;
+    }
+
+    SMOOTHER_REFERENCE_FRAME_SECONDS * 0.5f32.ln() / lag_weight.ln()
 }
 
 fn look_transform_system(
+    time: Res<Time>,
     mut cameras: Query<(&LookTransform, &mut Transform, Option<&mut Smoother>)>,
 ) {
+    let dt = time.delta_seconds();
     for (look_transform, mut scene_transform, smoother) in cameras.iter_mut() {
         let effective_look_transform = if let Some(mut smoother) = smoother {
-            smoother.smooth_transform(look_transform)
+            smoother.smooth_transform(look_transform, dt)
         } else {
             *look_transform
         };
-        *scene_transform = effective_look_transform.into();
+        if effective_look_transform.has_orientation() {
+            *scene_transform = effective_look_transform.into();
+        } else {
+            // Keep the last valid orientation rather than producing NaNs.
+            scene_transform.translation = effective_look_transform.eye;
+        }
+    }
+}
+
+// ████████╗███████╗███████╗████████╗
+// ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝
+//    ██║   █████╗  ███████╗   ██║
+//    ██║   ██╔══╝  ╚════██║   ██║
+//    ██║   ███████╗███████║   ██║
+//    ╚═╝   ╚══════╝╚══════╝   ╚═╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+
+    fn step_smoother(
+        smoother: &mut Smoother,
+        goal: &LookTransform,
+        dt: f32,
+        steps: usize,
+    ) -> LookTransform {
+        let mut smoothed = *goal;
+        for _ in 0..steps {
+            smoothed = smoother.smooth_transform(goal, dt);
+        }
+
+        smoothed
+    }
+
+    fn start_and_goal() -> (LookTransform, LookTransform) {
+        let start = LookTransform {
+            eye: Vec3::ZERO,
+            target: Vec3::Z,
+            up: Vec3::Y,
+        };
+        let goal = LookTransform {
+            eye: Vec3::new(10.0, 0.0, 0.0),
+            target: Vec3::new(10.0, 5.0, 1.0),
+            up: Vec3::Y,
+        };
+
+        (start, goal)
+    }
+
+    #[test]
+    fn test_smoothing_is_frame_rate_independent() {
+        let (start, goal) = start_and_goal();
+
+        let mut smoother_30 = Smoother::new(0.9);
+        smoother_30.smooth_transform(&start, 0.0);
+        let smoothed_30 = step_smoother(&mut smoother_30, &goal, 1.0 / 30.0, 15);
+
+        let mut smoother_60 = Smoother::new(0.9);
+        smoother_60.smooth_transform(&start, 0.0);
+        let smoothed_60 = step_smoother(&mut smoother_60, &goal, 1.0 / 60.0, 30);
+
+        let mut smoother_144 = Smoother::new(0.9);
+        smoother_144.smooth_transform(&start, 0.0);
+        let smoothed_144 = step_smoother(&mut smoother_144, &goal, 1.0 / 144.0, 72);
+
+        assert_relative_eq!(smoothed_30.eye.x, smoothed_60.eye.x, epsilon = 1e-4);
+        assert_relative_eq!(smoothed_30.target.y, smoothed_60.target.y, epsilon = 1e-4);
+        assert_relative_eq!(smoothed_144.eye.x, smoothed_60.eye.x, epsilon = 1e-4);
+        assert_relative_eq!(smoothed_144.target.y, smoothed_60.target.y, epsilon = 1e-4);
+
+        // Make sure we actually measured the transition and not the end state.
+        assert!(smoothed_60.eye.x < 9.9);
+    }
+
+    #[test]
+    fn test_lag_weight_is_per_reference_frame() {
+        let (start, goal) = start_and_goal();
+
+        let mut smoother = Smoother::new(0.9);
+        smoother.smooth_transform(&start, 0.0);
+        let smoothed = smoother.smooth_transform(&goal, SMOOTHER_REFERENCE_FRAME_SECONDS);
+
+        assert_relative_eq!(smoothed.eye.x, 1.0, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_half_life() {
+        let (start, goal) = start_and_goal();
+
+        let mut smoother = Smoother::from_half_life(0.25);
+        assert_relative_eq!(smoother.half_life(), 0.25, epsilon = 1e-5);
+        assert_relative_eq!(smoother.eye_half_life(), 0.25, epsilon = 1e-5);
+        assert_relative_eq!(smoother.target_half_life(), 0.25, epsilon = 1e-5);
+
+        smoother.smooth_transform(&start, 0.0);
+        let smoothed = step_smoother(&mut smoother, &goal, 0.05, 5);
+
+        assert_relative_eq!(smoothed.eye.x, 5.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_separate_eye_and_target_weights() {
+        let (start, goal) = start_and_goal();
+
+        let mut smoother = Smoother::new_separate(0.9, None, Some(0.0));
+        assert_relative_eq!(smoother.lag_weight(), 0.9);
+        assert_relative_eq!(smoother.eye_lag_weight(), 0.9);
+        assert_relative_eq!(smoother.target_lag_weight(), 0.0);
+        smoother.smooth_transform(&start, 0.0);
+        let smoothed = smoother.smooth_transform(&goal, SMOOTHER_REFERENCE_FRAME_SECONDS);
+
+        assert_relative_eq!(smoothed.eye.x, 1.0, epsilon = 1e-5);
+        assert_relative_eq!(smoothed.target.x, goal.target.x);
+        assert_relative_eq!(smoothed.target.y, goal.target.y);
+        assert_relative_eq!(smoothed.target.z, goal.target.z);
+    }
+
+    #[test]
+    fn test_spherical_smoothing_keeps_radius() {
+        let start = LookTransform {
+            eye: Vec3::new(0.0, 0.0, 5.0),
+            target: Vec3::ZERO,
+            up: Vec3::Y,
+        };
+        let goal = LookTransform {
+            eye: Vec3::new(0.0, 0.0, -5.0),
+            target: Vec3::ZERO,
+            up: Vec3::Y,
+        };
+
+        let mut smoother = Smoother::new(0.9);
+        smoother.set_mode(SmoothingMode::Spherical);
+        smoother.smooth_transform(&start, 0.0);
+        for _ in 0..30 {
+            let smoothed = smoother.smooth_transform(&goal, 1.0 / 60.0);
+            assert_relative_eq!(smoothed.radius(), 5.0, epsilon = 1e-4);
+        }
+
+        let smoothed = smoother.smooth_transform(&goal, 1.0 / 60.0);
+        assert!(smoothed.eye.z < 0.0);
+        // It swings around the vertical axis.
+        assert_relative_eq!(smoothed.eye.y, 0.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_spherical_smoothing_interpolates_radius() {
+        let start = LookTransform {
+            eye: Vec3::new(0.0, 0.0, 2.0),
+            target: Vec3::ZERO,
+            up: Vec3::Y,
+        };
+        let goal = LookTransform {
+            eye: Vec3::new(4.0, 0.0, 0.0),
+            target: Vec3::ZERO,
+            up: Vec3::Y,
+        };
+
+        let mut smoother = Smoother::from_half_life(0.1);
+        smoother.set_mode(SmoothingMode::Spherical);
+        smoother.smooth_transform(&start, 0.0);
+        let smoothed = smoother.smooth_transform(&goal, 0.1);
+
+        assert_relative_eq!(smoothed.radius(), 3.0, epsilon = 1e-3);
+        let angle = smoothed.eye.angle_between(Vec3::Z);
+        assert_relative_eq!(angle, std::f32::consts::FRAC_PI_4, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_reset_skips_smoothing() {
+        let (start, goal) = start_and_goal();
+
+        let mut smoother = Smoother::new(0.9);
+        smoother.smooth_transform(&start, 0.0);
+        smoother.reset();
+        let smoothed = smoother.smooth_transform(&goal, 1.0 / 60.0);
+
+        assert_relative_eq!(smoothed.eye.x, goal.eye.x);
+        assert_relative_eq!(smoothed.target.y, goal.target.y);
+    }
+
+    #[test]
+    fn test_snap_distance() {
+        let (start, goal) = start_and_goal();
+
+        let mut smoother = Smoother::new(0.9);
+        smoother.set_snap_distance(Some(20.0));
+        smoother.smooth_transform(&start, 0.0);
+        let smoothed = smoother.smooth_transform(&goal, 1.0 / 60.0);
+        assert!(smoothed.eye.x < goal.eye.x);
+
+        smoother.set_snap_distance(Some(5.0));
+        let smoothed = smoother.smooth_transform(&goal, 1.0 / 60.0);
+        assert_relative_eq!(smoothed.eye.x, goal.eye.x);
+    }
+
+    #[test]
+    fn test_snap_angle() {
+        let start = LookTransform {
+            eye: Vec3::ZERO,
+            target: Vec3::Z,
+            up: Vec3::Y,
+        };
+        let turned = LookTransform {
+            eye: Vec3::ZERO,
+            target: Vec3::X,
+            up: Vec3::Y,
+        };
+
+        let mut smoother = Smoother::new(0.9);
+        smoother.set_snap_angle(Some(1.0));
+        smoother.smooth_transform(&start, 0.0);
+        let smoothed = smoother.smooth_transform(&turned, 1.0 / 60.0);
+
+        assert_relative_eq!(smoothed.target.x, 1.0);
+        assert_relative_eq!(smoothed.target.z, 0.0);
+    }
+
+    #[test]
+    fn test_up_is_smoothed() {
+        let start = LookTransform::new(Vec3::ZERO, Vec3::Z, Vec3::Y);
+        let rolled = LookTransform::new(Vec3::ZERO, Vec3::Z, -Vec3::Y);
+
+        let mut smoother = Smoother::from_half_life(0.1);
+        smoother.smooth_transform(&start, 0.0);
+        let smoothed = smoother.smooth_transform(&rolled, 0.1);
+
+        // Halfway through a half turn around the look direction.
+        assert_relative_eq!(smoothed.up.length(), 1.0, epsilon = 1e-3);
+        assert_relative_eq!(smoothed.up.y, 0.0, epsilon = 1e-3);
+        assert_relative_eq!(smoothed.up.z, 0.0, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_rotation_smoothing_tumbles_over_the_top() {
+        // Tumble a quarter turn forward, from looking down at the target to looking at it from behind and below.
+        let start = LookTransform::new(Vec3::new(0.0, 5.0, 0.0), Vec3::ZERO, -Vec3::Z);
+        let goal = LookTransform::new(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y);
+
+        let mut smoother = Smoother::from_half_life(0.1);
+        smoother.set_mode(SmoothingMode::Rotation);
+        smoother.smooth_transform(&start, 0.0);
+        let smoothed = smoother.smooth_transform(&goal, 0.1);
+
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        assert_relative_eq!(smoothed.radius(), 5.0, epsilon = 1e-3);
+        assert_relative_eq!(smoothed.eye.y, 5.0 * half, epsilon = 1e-3);
+        assert_relative_eq!(smoothed.eye.z, 5.0 * half, epsilon = 1e-3);
+        // The up vector turned along with the eye, so it's still perpendicular to the look direction.
+        assert_relative_eq!(smoothed.up.y, half, epsilon = 1e-3);
+        assert_relative_eq!(smoothed.up.z, -half, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_rotation() {
+        let transform = LookTransform::new(Vec3::ZERO, Vec3::X, Vec3::Y);
+        let rotation = transform.rotation().unwrap();
+        let forward = rotation * -Vec3::Z;
+        assert_relative_eq!(forward.x, 1.0, epsilon = 1e-6);
+        assert_relative_eq!((rotation * Vec3::Y).y, 1.0, epsilon = 1e-6);
+
+        assert!(LookTransform::new(Vec3::ZERO, Vec3::Y, Vec3::Y)
+            .rotation()
+            .is_none());
+    }
+
+    #[test]
+    fn test_has_orientation() {
+        assert!(LookTransform::new(Vec3::ZERO, Vec3::Z, Vec3::Y).has_orientation());
+        assert!(!LookTransform::new(Vec3::ONE, Vec3::ONE, Vec3::Y).has_orientation());
+        assert!(!LookTransform::new(Vec3::ZERO, Vec3::Y, Vec3::Y).has_orientation());
+        assert!(!LookTransform::new(Vec3::ZERO, Vec3::Z, Vec3::ZERO).has_orientation());
+    }
+
+    #[test]
+    fn test_zero_dt_keeps_smoothed_transform() {
+        let (start, goal) = start_and_goal();
+
+        let mut smoother = Smoother::new(0.9);
+        smoother.smooth_transform(&start, 0.0);
+        let smoothed = smoother.smooth_transform(&goal, 0.0);
+
+        assert_relative_eq!(smoothed.eye.x, 0.0);
     }
 }
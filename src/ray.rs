@@ -0,0 +1,252 @@
+use bevy::{
+    math::prelude::*, render::camera::CameraProjection, transform::components::Transform,
+    window::Windows,
+};
+
+/// A half-line in world space, like the one that passes from the camera through the cursor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray3d {
+    pub origin: Vec3,
+    /// Always has unit length.
+    pub direction: Vec3,
+}
+
+impl Ray3d {
+    /// Returns `None` if the `direction` can't be normalized.
+    pub fn new(origin: Vec3, direction: Vec3) -> Option<Self> {
+        let direction = direction.normalize_or_zero();
+        if direction == Vec3::ZERO || !origin.is_finite() {
+            return None;
+        }
+
+        Some(Self { origin, direction })
+    }
+
+    /// The point at `distance` along the ray.
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + distance * self.direction
+    }
+
+    /// The ray from the camera through `cursor_position`, which is in logical pixels from the bottom-left corner of a window
+    /// with logical size `window_size`, like `Window::cursor_position`.
+    pub fn from_screenspace(
+        cursor_position: Vec2,
+        window_size: Vec2,
+        projection: &impl CameraProjection,
+        camera_transform: &Transform,
+    ) -> Option<Self> {
+        if window_size.x <= 0.0 || window_size.y <= 0.0 {
+            return None;
+        }
+        let ndc = 2.0 * cursor_position / window_size - Vec2::ONE;
+
+        // Unproject the cursor onto the near and far planes, which works for any projection.
+        let ndc_to_world =
+            camera_transform.compute_matrix() * projection.get_projection_matrix().inverse();
+        let near = ndc_to_world.project_point3(ndc.extend(0.0));
+        let far = ndc_to_world.project_point3(ndc.extend(1.0));
+
+        Self::new(near, far - near)
+    }
+
+    /// The ray from the camera through the cursor in the primary window, if the cursor is in that window.
+    pub fn from_cursor(
+        windows: &Windows,
+        projection: &impl CameraProjection,
+        camera_transform: &Transform,
+    ) -> Option<Self> {
+        let window = windows.get_primary()?;
+        let cursor_position = window.cursor_position()?;
+
+        Self::from_screenspace(
+            cursor_position,
+            Vec2::new(window.width(), window.height()),
+            projection,
+            camera_transform,
+        )
+    }
+
+    /// The distance along the ray to where it hits the plane through `plane_origin` with `plane_normal`. Returns `None` if the
+    /// ray is parallel to the plane or hits it behind the origin.
+    pub fn intersect_plane(&self, plane_origin: Vec3, plane_normal: Vec3) -> Option<f32> {
+        let denominator = self.direction.dot(plane_normal);
+        if denominator.abs() <= f32::EPSILON {
+            return None;
+        }
+        let distance = (plane_origin - self.origin).dot(plane_normal) / denominator;
+
+        (distance.is_finite() && distance >= 0.0).then_some(distance)
+    }
+}
+
+/// Finds the point in the scene that a ray hits, e.g. to pick the surface under the cursor. Implement this on top of your own
+/// physics or mesh queries; `PlanePicker` is a simple default.
+pub trait PointPicker: Send + Sync {
+    fn pick(&self, ray: &Ray3d) -> Option<Vec3>;
+}
+
+/// Picks points on an infinite plane, like the ground.
+///
+/// The default is the XZ plane through the origin, i.e. the ground of a Y-up scene. In a Z-up scene, use
+/// `PlanePicker::ground(Vec3::Z)` instead, or the default picks against a vertical wall.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlanePicker {
+    pub origin: Vec3,
+    pub normal: Vec3,
+}
+
+impl PlanePicker {
+    /// The ground plane through the origin of a scene whose up axis is `up`.
+    pub fn ground(up: Vec3) -> Self {
+        Self {
+            origin: Vec3::ZERO,
+            normal: up,
+        }
+    }
+}
+
+impl Default for PlanePicker {
+    fn default() -> Self {
+        Self::ground(Vec3::Y)
+    }
+}
+
+impl PointPicker for PlanePicker {
+    fn pick(&self, ray: &Ray3d) -> Option<Vec3> {
+        ray.intersect_plane(self.origin, self.normal)
+            .map(|distance| ray.at(distance))
+    }
+}
+
+// ████████╗███████╗███████╗████████╗
+// ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝
+//    ██║   █████╗  ███████╗   ██║
+//    ██║   ██╔══╝  ╚════██║   ██║
+//    ██║   ███████╗███████║   ██║
+//    ╚═╝   ╚══════╝╚══════╝   ╚═╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+    use bevy::{
+        math::const_vec2,
+        render::camera::{OrthographicProjection, PerspectiveProjection},
+    };
+
+    const WINDOW_SIZE: Vec2 = const_vec2!([800.0, 600.0]);
+
+    fn camera_transform() -> Transform {
+        Transform::from_translation(Vec3::new(0.0, 0.0, 10.0)).looking_at(Vec3::ZERO, Vec3::Y)
+    }
+
+    fn perspective() -> PerspectiveProjection {
+        let mut projection = PerspectiveProjection::default();
+        projection.update(WINDOW_SIZE.x, WINDOW_SIZE.y);
+        projection
+    }
+
+    fn orthographic() -> OrthographicProjection {
+        let mut projection = OrthographicProjection::default();
+        projection.update(WINDOW_SIZE.x, WINDOW_SIZE.y);
+        projection
+    }
+
+    #[test]
+    fn test_perspective_ray_through_center() {
+        let ray = Ray3d::from_screenspace(
+            0.5 * WINDOW_SIZE,
+            WINDOW_SIZE,
+            &perspective(),
+            &camera_transform(),
+        )
+        .unwrap();
+
+        assert_relative_eq!(ray.direction.x, 0.0, epsilon = 1e-4);
+        assert_relative_eq!(ray.direction.y, 0.0, epsilon = 1e-4);
+        assert_relative_eq!(ray.direction.z, -1.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_perspective_ray_through_corner_matches_fov() {
+        let projection = perspective();
+        let ray = Ray3d::from_screenspace(
+            Vec2::new(0.5 * WINDOW_SIZE.x, WINDOW_SIZE.y),
+            WINDOW_SIZE,
+            &projection,
+            &camera_transform(),
+        )
+        .unwrap();
+
+        // The ray through the top edge is half the vertical field of view above the look direction.
+        let angle = ray.direction.angle_between(-Vec3::Z);
+        assert_relative_eq!(angle, 0.5 * projection.fov, epsilon = 1e-4);
+        assert!(ray.direction.y > 0.0);
+    }
+
+    #[test]
+    fn test_orthographic_rays_are_parallel() {
+        let projection = orthographic();
+        let ray = Ray3d::from_screenspace(
+            Vec2::new(WINDOW_SIZE.x, 0.5 * WINDOW_SIZE.y),
+            WINDOW_SIZE,
+            &projection,
+            &camera_transform(),
+        )
+        .unwrap();
+
+        assert_relative_eq!(ray.direction.z, -1.0, epsilon = 1e-4);
+
+        // With the default scaling, one unit is one pixel, so the right edge is half the window width to the right.
+        let hit = ray.at(ray.intersect_plane(Vec3::ZERO, Vec3::Z).unwrap());
+        assert_relative_eq!(hit.x, 0.5 * WINDOW_SIZE.x, epsilon = 1e-2);
+        assert_relative_eq!(hit.y, 0.0, epsilon = 1e-2);
+    }
+
+    #[test]
+    fn test_intersect_plane() {
+        let ray = Ray3d::new(Vec3::new(1.0, 5.0, 0.0), -Vec3::Y).unwrap();
+        assert_relative_eq!(ray.intersect_plane(Vec3::ZERO, Vec3::Y).unwrap(), 5.0);
+
+        // Behind the origin.
+        assert_eq!(
+            ray.intersect_plane(Vec3::new(0.0, 10.0, 0.0), Vec3::Y),
+            None
+        );
+
+        // Parallel.
+        assert_eq!(ray.intersect_plane(Vec3::ZERO, Vec3::X), None);
+    }
+
+    #[test]
+    fn test_plane_picker() {
+        let picker = PlanePicker::default();
+
+        let ray = Ray3d::new(Vec3::new(0.0, 4.0, 4.0), Vec3::new(1.0, -1.0, -1.0)).unwrap();
+        let point = picker.pick(&ray).unwrap();
+        assert_relative_eq!(point.x, 4.0, epsilon = 1e-4);
+        assert_relative_eq!(point.y, 0.0, epsilon = 1e-4);
+        assert_relative_eq!(point.z, 0.0, epsilon = 1e-4);
+
+        // Looking at the sky.
+        let ray = Ray3d::new(Vec3::new(0.0, 4.0, 4.0), Vec3::Y).unwrap();
+        assert_eq!(picker.pick(&ray), None);
+    }
+
+    #[test]
+    fn test_z_up_ground_picker() {
+        let picker = PlanePicker::ground(Vec3::Z);
+
+        let ray = Ray3d::new(Vec3::new(0.0, 4.0, 4.0), Vec3::new(1.0, -1.0, -1.0)).unwrap();
+        let point = picker.pick(&ray).unwrap();
+        assert_relative_eq!(point.x, 4.0, epsilon = 1e-4);
+        assert_relative_eq!(point.y, 0.0, epsilon = 1e-4);
+        assert_relative_eq!(point.z, 0.0, epsilon = 1e-4);
+
+        // Looking straight down in a Z-up scene still hits the ground.
+        let ray = Ray3d::new(Vec3::new(1.0, 2.0, 4.0), -Vec3::Z).unwrap();
+        let point = picker.pick(&ray).unwrap();
+        assert_relative_eq!(point.z, 0.0, epsilon = 1e-4);
+    }
+}